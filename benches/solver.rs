@@ -0,0 +1,117 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use moogan_course_scheduler::parse::{CourseListContext, SectionMeeting};
+use moogan_course_scheduler::solver::{BTSolver, CoursePreferences};
+use moogan_course_scheduler::utils::BitSet;
+use serde_json::{json, Value};
+
+const N_COURSES: u64 = 6;
+const N_LABS: u64 = 12;
+const N_DISCUSSIONS: u64 = 6;
+
+// small deterministic lcg so every run builds the same catalog
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self, bound: u64) -> u64 {
+        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (self.0 >> 33) % bound
+    }
+}
+
+fn meeting(start: u64, days: u64, meeting_type: u64, length: u64) -> Value {
+    let end = start + length;
+    json!({
+        "inSession": days,
+        "meetingType": meeting_type,
+        "beginTime": format!("{:0>2}{:0>2}", start / 60, start % 60),
+        "endTime": format!("{:0>2}{:0>2}", end / 60, end % 60)
+    })
+}
+
+// every course gets a twice a week lecture that never overlaps another lecture, weekly labs and weekly discussions on random days
+fn synthetic_catalog() -> (Value, Vec<u64>) {
+    let mut rng = Lcg(2023);
+    let mut nodes = Vec::new();
+    let mut want = Vec::new();
+
+    for course in 0..N_COURSES {
+        let lecture_id = (course + 1) * 100;
+        let linked: Vec<u64> = (1..=N_LABS + N_DISCUSSIONS).map(|i| lecture_id + i).collect();
+        let days = if course % 2 == 0 { (1 << 1) | (1 << 3) } else { (1 << 2) | (1 << 4) };
+
+        nodes.push(json!({
+            "id": lecture_id,
+            "courseNumber": format!("SYN-{}-01", course),
+            "linkedSections": linked.iter().map(|id| json!({ "parent": id })).collect::<Vec<_>>(),
+            "meetings": [meeting(8 * 60 + course * 90, days, 1, 75)]
+        }));
+
+        for (i, section_id) in linked.iter().enumerate() {
+            let is_lab = (i as u64) < N_LABS;
+            let day = 1 << (1 + rng.next(5));
+            let start = 8 * 60 + rng.next(20) * 30;
+            nodes.push(json!({
+                "id": section_id,
+                "courseNumber": format!("SYN-{}-{:0>2}{}", course, i + 2, if is_lab { "L" } else { "D" }),
+                "linkedSections": [],
+                "meetings": [meeting(start, day, if is_lab { 2 } else { 3 }, if is_lab { 110 } else { 50 })]
+            }));
+        }
+
+        want.push(lecture_id);
+    }
+
+    let catalog = json!({
+        "meetingTypes": [
+            { "id": 1, "name": "Lecture" },
+            { "id": 2, "name": "Lab" },
+            { "id": 3, "name": "Discussion" }
+        ],
+        "classes": { "nodes": nodes }
+    });
+
+    (catalog, want)
+}
+
+fn solve_synthetic(c: &mut Criterion) {
+    let (catalog, want) = synthetic_catalog();
+    let solver = BTSolver::new(CoursePreferences::new(want, CourseListContext::from_value(&catalog).unwrap()).unwrap());
+
+    c.bench_function("solve synthetic", |b| b.iter(|| solver.solve()));
+}
+
+// whether each section fits next to a partial schedule, by scanning the schedule the way the
+// solver used to and by the conflict rows it precomputes now
+fn conflict_check(c: &mut Criterion) {
+    let (catalog, want) = synthetic_catalog();
+    let sections = CourseListContext::from_value(&catalog).unwrap().meetings_from_lectures(&want).unwrap();
+
+    let mut rows = vec![BitSet::new(sections.len()); sections.len()];
+    for (i, section) in sections.iter().enumerate() {
+        for (j, other) in sections.iter().enumerate() {
+            if i != j && section.conflicts_with(other) { rows[i].insert(j); }
+        }
+    }
+
+    // every lecture meeting plus a lab and a discussion of each course, about what a schedule holds
+    let mut rng = Lcg(150);
+    let mut chosen: Vec<usize> = (0..sections.len()).filter(|&idx| sections[idx].section_id == sections[idx].lecture_id).collect();
+    chosen.extend((0..2 * N_COURSES).map(|_| rng.next(sections.len() as u64) as usize));
+    let chosen_sections: Vec<&SectionMeeting> = chosen.iter().map(|&idx| &sections[idx]).collect();
+    let mut chosen_mask = BitSet::new(sections.len());
+    chosen.iter().for_each(|&idx| chosen_mask.insert(idx));
+
+    let mut group = c.benchmark_group("conflict check");
+    group.bench_function("scan", |b| b.iter(|| {
+        sections.iter().filter(|section| !chosen_sections.iter().any(|other| section.conflicts_with(other))).count()
+    }));
+    group.bench_function("bitset", |b| b.iter(|| {
+        rows.iter().filter(|row| !row.intersects(black_box(&chosen_mask))).count()
+    }));
+    group.finish();
+}
+
+criterion_group!(benches, solve_synthetic, conflict_check);
+criterion_main!(benches);
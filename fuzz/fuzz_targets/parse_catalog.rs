@@ -0,0 +1,25 @@
+#![no_main]
+
+// any json, valid or not, has to come back as Ok or a SolveError, never a panic
+// cargo +nightly fuzz run parse_catalog
+
+use libfuzzer_sys::fuzz_target;
+use moogan_course_scheduler::parse::CourseListContext;
+use moogan_course_scheduler::solver::CoursePreferences;
+use serde_json::Value;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(gql_response) = serde_json::from_slice::<Value>(data) else { return };
+    let Ok(ctx) = CourseListContext::from_value(&gql_response) else { return };
+
+    // every id in the catalog plus a few that are not
+    let mut want: Vec<u64> = ctx.id_to_course.keys().copied().collect();
+    want.sort();
+    want.extend([0, u64::MAX]);
+
+    for lecture_id in &want {
+        let _ = ctx.meetings_from_lectures(&[*lecture_id]);
+    }
+    let _ = ctx.meetings_from_lectures(&want);
+    let _ = CoursePreferences::new(want, ctx);
+});
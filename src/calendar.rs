@@ -0,0 +1,169 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+// bit i of a meeting's inSession is day i, sunday first. the api doesn't document it, the
+// parse tests check it against the dates of the fixture's finals
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Weekday {
+    Sunday,
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+}
+
+impl Weekday {
+    pub const ALL: [Weekday; 7] = [Self::Sunday, Self::Monday, Self::Tuesday, Self::Wednesday,
+        Self::Thursday, Self::Friday, Self::Saturday];
+
+    pub fn from_index(idx: u64) -> Option<Self> {
+        Self::ALL.get(idx as usize).copied()
+    }
+
+    pub fn index(self) -> u64 {
+        self as u64
+    }
+
+    // days set in an inSession bit field, bits past saturday are left out
+    pub fn from_bits(bits: u64) -> Vec<Self> {
+        Self::ALL.into_iter().filter(|day| bits & (1 << day.index()) != 0).collect()
+    }
+
+    pub fn abbrev(self) -> &'static str {
+        ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"][self as usize]
+    }
+
+    pub fn ics_code(self) -> &'static str {
+        ["SU", "MO", "TU", "WE", "TH", "FR", "SA"][self as usize]
+    }
+}
+
+// plain calendar date, the catalog only gives days without times or zones
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    pub fn new(year: i32, month: u32, day: u32) -> Option<Self> {
+        let date = Self { year, month, day };
+        if month == 0 || month > 12 || day == 0 || day > date.days_in_month() { return None; }
+        Some(date)
+    }
+
+    // accepts 2024-08-26 and 08/26/2024
+    pub fn parse(raw: &str) -> Option<Self> {
+        let parts: Vec<&str> = raw.trim().split(['-', '/']).collect();
+        let [a, b, c] = parts.as_slice() else { return None };
+
+        if raw.contains('/') {
+            Self::new(c.parse().ok()?, a.parse().ok()?, b.parse().ok()?)
+        } else {
+            Self::new(a.parse().ok()?, b.parse().ok()?, c.parse().ok()?)
+        }
+    }
+
+    fn is_leap_year(&self) -> bool {
+        (self.year % 4 == 0 && self.year % 100 != 0) || self.year % 400 == 0
+    }
+
+    fn days_in_month(&self) -> u32 {
+        match self.month {
+            2 if self.is_leap_year() => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            _ => 31,
+        }
+    }
+
+    // days since 1970-01-01, from howard hinnant's days_from_civil
+    pub fn to_days(&self) -> i64 {
+        let year = self.year as i64 - if self.month <= 2 { 1 } else { 0 };
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let month = self.month as i64;
+        let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + self.day as i64 - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+        era * 146097 + day_of_era - 719468
+    }
+
+    pub fn from_days(days: i64) -> Self {
+        let days = days + 719468;
+        let era = days.div_euclid(146097);
+        let day_of_era = days - era * 146097;
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = (year_of_era + era * 400 + if month <= 2 { 1 } else { 0 }) as i32;
+
+        Self { year, month, day }
+    }
+
+    pub fn add_days(&self, days: i64) -> Self {
+        Self::from_days(self.to_days() + days)
+    }
+
+    pub fn weekday(&self) -> Weekday {
+        // 1970-01-01 was a thursday
+        Weekday::ALL[(self.to_days() + 4).rem_euclid(7) as usize]
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl TryFrom<String> for Date {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::parse(&value).ok_or_else(|| format!("invalid date {:?}", value))
+    }
+}
+
+impl From<Date> for String {
+    fn from(value: Date) -> Self {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::calendar::{Date, Weekday};
+
+    #[test]
+    fn in_session_bits() {
+        assert_eq!(Weekday::from_bits(1), vec![Weekday::Sunday]);
+        // monday and wednesday
+        assert_eq!(Weekday::from_bits(0b1010), vec![Weekday::Monday, Weekday::Wednesday]);
+        assert_eq!(Weekday::from_bits(1 << 6), vec![Weekday::Saturday]);
+        assert_eq!(Weekday::from_bits(1 << 7), vec![]);
+    }
+
+    #[test]
+    fn parses_both_formats() {
+        assert_eq!(Date::parse("2024-08-26"), Date::new(2024, 8, 26));
+        assert_eq!(Date::parse("08/26/2024"), Date::new(2024, 8, 26));
+        assert_eq!(Date::parse("2023-02-29"), None);
+        assert_eq!(Date::parse("TBA"), None);
+    }
+
+    #[test]
+    fn day_arithmetic() {
+        let date = Date::new(2024, 2, 27).unwrap();
+        assert_eq!(date.add_days(3), Date::new(2024, 3, 1).unwrap());
+        assert_eq!(Date::from_days(0), Date::new(1970, 1, 1).unwrap());
+        assert_eq!(Date::new(2024, 8, 26).unwrap().weekday(), Weekday::Monday);
+    }
+}
@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+
+use crate::calendar::Weekday;
+use crate::parse::SectionMeeting;
+
+const DAY: u64 = 24 * 3600;
+
+// hard limits on which weekly meetings may be taken, times are seconds after midnight.
+// exams are left alone since taking the course means taking its final
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum Constraint {
+    // keep start..end free on `day`, or on every day without one
+    BlockedInterval { day: Option<Weekday>, start: u64, end: u64 },
+    BlockedDay { day: Weekday },
+    EarliestStart { time: u64 },
+    LatestEnd { time: u64 },
+    // no full sections, unless at most max_waitlist students are already waiting
+    OpenSeats { max_waitlist: Option<u64> },
+    // no sections taught by any of these
+    AvoidInstructors { names: Vec<String> },
+}
+
+impl Constraint {
+    pub fn excludes(&self, section: &SectionMeeting) -> bool {
+        if section.is_exam() { return false; }
+
+        let start = section.u_start % DAY;
+        let end = section.u_end % DAY;
+
+        match self {
+            Self::BlockedInterval { day, start: blocked_start, end: blocked_end } =>
+                day.is_none_or(|day| day == section.weekday()) && start < *blocked_end && *blocked_start < end,
+            Self::BlockedDay { day } => *day == section.weekday(),
+            Self::EarliestStart { time } => start < *time,
+            Self::LatestEnd { time } => end > *time,
+            Self::OpenSeats { max_waitlist } => section.is_full()
+                && max_waitlist.is_none_or(|max_waitlist| section.waitlist.is_some_and(|waitlist| waitlist > max_waitlist)),
+            Self::AvoidInstructors { names } => section.taught_by_any(names),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::calendar::Weekday;
+    use crate::constraint::Constraint;
+    use crate::parse::{MeetingType, SectionMeeting};
+
+    const DAY: u64 = 24 * 3600;
+
+    // friday 12:00 - 12:50
+    fn friday_noon(meeting_type: MeetingType) -> SectionMeeting {
+        SectionMeeting {
+            u_start: 5 * DAY + 12 * 3600, u_end: 5 * DAY + 12 * 3600 + 3000,
+            section_id: 1, lecture_id: 1, section_name: "CSE-150-01".into(), meeting_type,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn excludes_weekly_meetings_only() {
+        let lecture = friday_noon(MeetingType::Lecture);
+        let lunch = Constraint::BlockedInterval { day: None, start: 12 * 3600, end: 13 * 3600 };
+
+        assert!(lunch.excludes(&lecture));
+        assert!(Constraint::BlockedDay { day: Weekday::Friday }.excludes(&lecture));
+        assert!(!Constraint::BlockedDay { day: Weekday::Monday }.excludes(&lecture));
+        assert!(!Constraint::BlockedInterval { day: Some(Weekday::Monday), start: 12 * 3600, end: 13 * 3600 }.excludes(&lecture));
+        // ending right when the block starts is fine
+        assert!(!Constraint::BlockedInterval { day: None, start: 12 * 3600 + 3000, end: 13 * 3600 }.excludes(&lecture));
+        assert!(Constraint::EarliestStart { time: 13 * 3600 }.excludes(&lecture));
+        assert!(!Constraint::LatestEnd { time: 13 * 3600 }.excludes(&lecture));
+
+        assert!(!lunch.excludes(&friday_noon(MeetingType::Exam)));
+    }
+
+    #[test]
+    fn full_sections() {
+        let mut lecture = friday_noon(MeetingType::Lecture);
+        let no_full = Constraint::OpenSeats { max_waitlist: None };
+        let short_waitlist = Constraint::OpenSeats { max_waitlist: Some(5) };
+
+        // nothing known about seats
+        assert!(!no_full.excludes(&lecture));
+
+        lecture.open_seats = Some(3);
+        assert!(!no_full.excludes(&lecture));
+
+        lecture.open_seats = Some(0);
+        lecture.waitlist = Some(4);
+        assert!(no_full.excludes(&lecture));
+        assert!(!short_waitlist.excludes(&lecture));
+
+        lecture.waitlist = Some(6);
+        assert!(short_waitlist.excludes(&lecture));
+    }
+
+    #[test]
+    fn avoided_instructors() {
+        let mut lecture = friday_noon(MeetingType::Lecture);
+        lecture.instructors = vec!["Ada Lovelace".into(), "Alan Turing".into()];
+
+        assert!(Constraint::AvoidInstructors { names: vec![" alan turing".into()] }.excludes(&lecture));
+        assert!(!Constraint::AvoidInstructors { names: vec!["Grace Hopper".into()] }.excludes(&lecture));
+    }
+}
@@ -0,0 +1,188 @@
+use crate::calendar::{Date, Weekday};
+use crate::parse::{MeetingType, SectionMeeting};
+use crate::utils::{now_ms, SolveError};
+
+// RFC 5545 export of one schedule. weekly meetings become one recurring VEVENT per section and
+// time slot, exams become single events on their date. times are floating (campus local time)
+
+const DAY: u64 = 24 * 3600;
+
+// meetings of the same section at the same time on different days share one event
+struct WeeklyEvent<'a> {
+    meeting: &'a SectionMeeting,
+    start_date: Date,
+    end_date: Date,
+    days: Vec<Weekday>,
+}
+
+pub fn schedule_to_ics(schedule: &[SectionMeeting]) -> Result<String, SolveError> {
+    calendar_at(schedule, now_ms())
+}
+
+// created_ms is when the calendar is made, milliseconds since 1970 in utc
+fn calendar_at(schedule: &[SectionMeeting], created_ms: f64) -> Result<String, SolveError> {
+    let stamp = ics_stamp(created_ms);
+    let mut weekly: Vec<WeeklyEvent> = Vec::new();
+    let mut exams: Vec<(&SectionMeeting, Date)> = Vec::new();
+
+    for meeting in schedule {
+        let (Some(start_date), Some(end_date)) = (meeting.start_date, meeting.end_date) else {
+            return Err(SolveError::MissingDates { section_id: meeting.section_id });
+        };
+        let day = meeting.weekday();
+
+        if matches!(meeting.meeting_type, MeetingType::Exam) {
+            if !exams.iter().any(|(exam, date)| exam.section_id == meeting.section_id && *date == start_date) {
+                exams.push((meeting, start_date));
+            }
+            continue;
+        }
+
+        let same_slot = weekly.iter_mut().find(|event| {
+            event.meeting.section_id == meeting.section_id
+                && event.meeting.u_start % DAY == meeting.u_start % DAY
+                && event.meeting.u_end % DAY == meeting.u_end % DAY
+                && event.start_date == start_date && event.end_date == end_date
+        });
+        match same_slot {
+            Some(event) => if !event.days.contains(&day) { event.days.push(day) },
+            None => weekly.push(WeeklyEvent { meeting, start_date, end_date, days: vec![day] }),
+        }
+    }
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//ReiMoogan//moogan course scheduler//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+
+    for event in &mut weekly {
+        event.days.sort();
+        // first day on or after the term start that the meeting happens
+        let first = (0..7).map(|offset| event.start_date.add_days(offset))
+            .find(|date| event.days.contains(&date.weekday()))
+            .unwrap_or(event.start_date);
+        let by_day: Vec<&str> = event.days.iter().map(|day| day.ics_code()).collect();
+        let rrule = format!("FREQ=WEEKLY;BYDAY={};UNTIL={}T235959", by_day.join(","), ics_date(event.end_date));
+
+        push_event(&mut lines, event.meeting, first, &stamp, &by_day.join(""), Some(rrule));
+    }
+
+    // one section can have exams at the same time on different days
+    for (exam, date) in exams {
+        push_event(&mut lines, exam, date, &stamp, &format!("{}-EXAM", ics_date(date)), None);
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+
+    Ok(lines.iter().map(|line| fold(line)).collect::<Vec<_>>().join(""))
+}
+
+fn push_event(lines: &mut Vec<String>, meeting: &SectionMeeting, date: Date, stamp: &str, uid_suffix: &str, rrule: Option<String>) {
+    lines.push("BEGIN:VEVENT".to_string());
+    lines.push(format!("UID:{}-{}-{}@moogan", meeting.section_id, meeting.u_start % DAY, uid_suffix));
+    lines.push(format!("DTSTAMP:{}", stamp));
+    lines.push(format!("DTSTART:{}T{}", ics_date(date), ics_time(meeting.u_start)));
+    lines.push(format!("DTEND:{}T{}", ics_date(date), ics_time(meeting.u_end)));
+    lines.extend(rrule.map(|rrule| format!("RRULE:{}", rrule)));
+    lines.push(format!("SUMMARY:{}", escape(&format!("{} {:?}", meeting.section_name, meeting.meeting_type))));
+    lines.push("END:VEVENT".to_string());
+}
+
+fn ics_date(date: Date) -> String {
+    format!("{:04}{:02}{:02}", date.year, date.month, date.day)
+}
+
+// utc date and time, unlike the floating event times
+fn ics_stamp(ms: f64) -> String {
+    let seconds = (ms / 1000.0) as i64;
+    let date = Date::from_days(seconds.div_euclid(DAY as i64));
+    format!("{}T{}Z", ics_date(date), ics_time(seconds.rem_euclid(DAY as i64) as u64))
+}
+
+fn ics_time(u_time: u64) -> String {
+    let seconds = u_time % DAY;
+    format!("{:02}{:02}{:02}", seconds / 3600, seconds % 3600 / 60, seconds % 60)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace(';', "\\;").replace(',', "\\,").replace('\n', "\\n")
+}
+
+// lines longer than 75 octets continue on the next line after a space
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut width = 0;
+
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+
+    folded.push_str("\r\n");
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::calendar::Date;
+    use crate::ics::{calendar_at, schedule_to_ics};
+    use crate::parse::{MeetingType, SectionMeeting};
+    use crate::utils::SolveError;
+
+    const DAY: u64 = 24 * 3600;
+
+    fn meeting(day: u64, start: u64, end: u64, meeting_type: MeetingType, dates: (&str, &str)) -> SectionMeeting {
+        SectionMeeting {
+            u_start: day * DAY + start, u_end: day * DAY + end,
+            section_id: 1, lecture_id: 1,
+            section_name: "CSE-150-01".into(), meeting_type,
+            start_date: Date::parse(dates.0), end_date: Date::parse(dates.1),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn weekly_lecture_and_exam() {
+        let term = ("2024-08-28", "2024-12-06");
+        let schedule = vec![
+            meeting(1, 10 * 3600 + 1800, 11 * 3600 + 2700, MeetingType::Lecture, term),
+            meeting(3, 10 * 3600 + 1800, 11 * 3600 + 2700, MeetingType::Lecture, term),
+            meeting(2, 8 * 3600, 11 * 3600, MeetingType::Exam, ("2024-12-10", "2024-12-10")),
+        ];
+        let ics = schedule_to_ics(&schedule).unwrap();
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n") && ics.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 2);
+        // the term starts on a wednesday, so that is the first lecture
+        assert!(ics.contains("DTSTART:20240828T103000\r\nDTEND:20240828T114500\r\n\
+            RRULE:FREQ=WEEKLY;BYDAY=MO,WE;UNTIL=20241206T235959\r\n"));
+        assert!(ics.contains("DTSTART:20241210T080000\r\nDTEND:20241210T110000\r\nSUMMARY:CSE-150-01 Exam\r\n"));
+    }
+
+    #[test]
+    fn stamps_and_exam_uids() {
+        let schedule = vec![
+            meeting(2, 8 * 3600, 11 * 3600, MeetingType::Exam, ("2024-12-10", "2024-12-10")),
+            meeting(4, 8 * 3600, 11 * 3600, MeetingType::Exam, ("2024-12-12", "2024-12-12")),
+        ];
+        // 2024-12-10 08:40 utc
+        let ics = calendar_at(&schedule, 1_733_820_000_000.0).unwrap();
+
+        assert_eq!(ics.matches("DTSTAMP:20241210T084000Z\r\n").count(), 2);
+        assert!(ics.contains("UID:1-28800-20241210-EXAM@moogan\r\n"));
+        assert!(ics.contains("UID:1-28800-20241212-EXAM@moogan\r\n"));
+    }
+
+    #[test]
+    fn needs_dates() {
+        let mut lecture = meeting(1, 0, 3600, MeetingType::Lecture, ("", ""));
+        lecture.section_id = 7;
+        assert_eq!(schedule_to_ics(&[lecture]), Err(SolveError::MissingDates { section_id: 7 }));
+    }
+}
@@ -1,11 +1,24 @@
-use parse::CourseListContext;
-use solver::{BTSolver, CoursePreferences};
-use serde_json::Value;
+use constraint::Constraint;
+use model::GqlResponse;
+use parse::{CourseListContext, WantedCourse};
+use serde::{Deserialize, Serialize};
+use solver::{BTSolver, CancelFlag, CoursePreferences, Schedule, SchedulePreferences, Schedules, SolveOptions};
+use typescript::{JsCatalog, JsClassNode, JsClassNodes, JsConstraints, JsProgress, JsSchedule,
+    JsSchedulePreferences, JsSchedules, JsSearchStatus, JsSolveOptions, JsSolveResult, JsWalkingTimes, JsWant};
+use utils::{now_ms, ErrorReport, SolveError};
+use walking::WalkingTimes;
 use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
 
-mod parse;
-mod solver;
-mod utils;
+pub mod calendar;
+pub mod constraint;
+pub mod ics;
+pub mod model;
+pub mod parse;
+pub mod solver;
+pub mod typescript;
+pub mod utils;
+pub mod walking;
 
 #[wasm_bindgen]
 extern {
@@ -14,29 +27,263 @@ extern {
     fn alert(s: &str);
 }
 
+// want is an array of lecture ids, course codes like "CSE-150" and { course, optional } objects,
+// or { courses: [...], minCourses, maxCourses, minUnits, maxUnits } to limit how much gets taken.
+// walking times are an optional { building: { building: seconds } } table, or
+// { times: table, strict: true } to rule out transitions that are too short instead of penalizing them.
+// returns schedules best first. errors are thrown as { kind, message, ...ids } objects, including
+// kind "noSchedule" with an explanation when nothing fits. typescript.rs has the exact shapes
 #[wasm_bindgen]
-pub fn solve(gql_response_js_obj: JsValue, want: Vec<u64>) -> JsValue {
-    
-    let serde_gql_response: Value = serde_wasm_bindgen::from_value(gql_response_js_obj).unwrap();
-    // serde_wasm_bindgen::to_value(&vec![vec![SampleStruct::default()]]).unwrap()
-    // vec![SampleStruct::default(), SampleStruct::default()]
-    // vec![vec![SampleStruct::default(), SampleStruct::default()]]
-    // let res = fs::read("data/mess.json");
-    // let gql_response = serde_json::from_str(std::str::from_utf8(&res.unwrap()).unwrap()).unwrap();
-    
-    // let gql_response_json: Value = serde_json::from_str(&r).unwrap();
-    let ctx = match CourseListContext::new(&serde_gql_response) {
-        Ok(ctx) => ctx,
-        Err(err) => return serde_wasm_bindgen::to_value(err.msg).unwrap()
+pub fn solve(catalog: JsCatalog, want: JsWant, schedule_prefs: JsSchedulePreferences, constraints: JsConstraints,
+    walking_times: JsWalkingTimes) -> Result<JsSchedules, JsValue> {
+    let res = parse_catalog(catalog)
+        .and_then(|ctx| build_solver(&ctx, want, schedule_prefs, constraints, walking_times))
+        .and_then(|solver| solve_all(&solver));
+
+    res.map_err(|err| error_value(&err))
+}
+
+// solve with { maxSolutions, maxNodes, deadline } limits, deadline in milliseconds.
+// options.onProgress, if given, gets { nodes, found, remaining } every so often and can return
+// false to stop, which is how a worker can check for a cancel message. status in the result is
+// "exhaustive" or why it stopped. throws like solve or whatever onProgress threw, but an empty
+// result only counts as an error when the search was exhaustive
+#[wasm_bindgen]
+pub fn solve_with_options(catalog: JsCatalog, want: JsWant, schedule_prefs: JsSchedulePreferences, constraints: JsConstraints,
+    walking_times: JsWalkingTimes, options: JsSolveOptions) -> Result<JsSolveResult, JsValue> {
+    let solver = parse_catalog(catalog)
+        .and_then(|ctx| build_solver(&ctx, want, schedule_prefs, constraints, walking_times))
+        .map_err(|err| error_value(&err))?;
+
+    solve_limited(&solver, options)
+}
+
+// same arguments as solve_with_options, but hands back schedules a page at a time in the order
+// they're found instead of sorted, so the first ones show up before the whole search is done.
+// the limits count across batches, and cancelling the flag between batches ends the stream
+#[wasm_bindgen]
+pub fn solve_stream(catalog: JsCatalog, want: JsWant, schedule_prefs: JsSchedulePreferences, constraints: JsConstraints,
+    walking_times: JsWalkingTimes, options: JsSolveOptions, cancel: &CancelFlag) -> Result<ScheduleStream, JsValue> {
+    parse_catalog(catalog)
+        .and_then(|ctx| build_solver(&ctx, want, schedule_prefs, constraints, walking_times))
+        .and_then(|solver| stream(solver, options, cancel))
+        .map_err(|err| error_value(&err))
+}
+
+// the catalog parsed once and kept around, so solving again after every change doesn't
+// convert and index the whole thing each time. methods take the same arguments as the free
+// functions minus the catalog
+#[wasm_bindgen]
+pub struct Scheduler {
+    ctx: CourseListContext,
+}
+
+#[wasm_bindgen]
+impl Scheduler {
+    #[wasm_bindgen(constructor)]
+    pub fn new(catalog: JsCatalog) -> Result<Scheduler, JsValue> {
+        parse_catalog(catalog)
+            .map(|ctx| Scheduler { ctx })
+            .map_err(|err| error_value(&err))
+    }
+
+    pub fn solve(&self, want: JsWant, schedule_prefs: JsSchedulePreferences, constraints: JsConstraints,
+        walking_times: JsWalkingTimes) -> Result<JsSchedules, JsValue> {
+        build_solver(&self.ctx, want, schedule_prefs, constraints, walking_times)
+            .and_then(|solver| solve_all(&solver))
+            .map_err(|err| error_value(&err))
+    }
+
+    pub fn solve_with_options(&self, want: JsWant, schedule_prefs: JsSchedulePreferences, constraints: JsConstraints,
+        walking_times: JsWalkingTimes, options: JsSolveOptions) -> Result<JsSolveResult, JsValue> {
+        let solver = build_solver(&self.ctx, want, schedule_prefs, constraints, walking_times)
+            .map_err(|err| error_value(&err))?;
+
+        solve_limited(&solver, options)
+    }
+
+    pub fn solve_stream(&self, want: JsWant, schedule_prefs: JsSchedulePreferences, constraints: JsConstraints,
+        walking_times: JsWalkingTimes, options: JsSolveOptions, cancel: &CancelFlag) -> Result<ScheduleStream, JsValue> {
+        build_solver(&self.ctx, want, schedule_prefs, constraints, walking_times)
+            .and_then(|solver| stream(solver, options, cancel))
+            .map_err(|err| error_value(&err))
+    }
+
+    // the catalog entry for a section id as it was passed in, undefined if there's none
+    pub fn course(&self, id: f64) -> Result<JsClassNode, JsValue> {
+        let course = (id >= 0.0 && id.fract() == 0.0).then(|| self.ctx.id_to_course.get(&(id as u64))).flatten();
+        match course {
+            Some(course) => to_js(course).map_err(|err| error_value(&err)),
+            None => Ok(JsValue::UNDEFINED.unchecked_into()),
+        }
+    }
+
+    // lecture sections whose course number matches query, e.g. "cse 150", as catalog entries
+    pub fn search(&self, query: &str) -> Result<JsClassNodes, JsValue> {
+        to_js(&self.ctx.search(query)).map_err(|err| error_value(&err))
+    }
+}
+
+// a plain table only penalizes tight transitions, { times, strict: true } rules them out
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Walking {
+    Strict { times: WalkingTimes, strict: bool },
+    Times(WalkingTimes),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Want {
+    Courses(Vec<WantedCourse>),
+    #[serde(rename_all = "camelCase")]
+    Selection {
+        courses: Vec<WantedCourse>,
+        min_courses: Option<usize>,
+        max_courses: Option<usize>,
+        min_units: Option<f64>,
+        max_units: Option<f64>,
+    },
+}
+
+fn solve_all(solver: &BTSolver) -> Result<JsSchedules, SolveError> {
+    let res = solver.solve();
+    if let Some(explanation) = res.is_empty().then(|| solver.explain_infeasible_with(&SolveOptions::default())).flatten() {
+        return Err(SolveError::NoSchedule { explanation });
+    }
+
+    to_js(&res)
+}
+
+fn solve_limited(solver: &BTSolver, options: JsSolveOptions) -> Result<JsSolveResult, JsValue> {
+    let on_progress = options.is_object().then(|| options.on_progress()).filter(|on_progress| on_progress.is_function());
+    let options = solve_options(options).map_err(|err| error_value(&err))?;
+    let started = now_ms();
+
+    // anything but an explicit false keeps going, a throw stops the search and is rethrown
+    let mut thrown = None;
+    let res = match on_progress {
+        Some(on_progress) => solver.solve_with_progress(&options, |progress| {
+            if thrown.is_some() { return false; }
+            let returned = to_js::<JsProgress>(progress)
+                .map_err(|err| error_value(&err))
+                .and_then(|progress| on_progress.call(&JsValue::UNDEFINED, &progress));
+            match returned {
+                Ok(returned) => returned.as_bool() != Some(false),
+                Err(err) => {
+                    thrown = Some(err);
+                    false
+                }
+            }
+        }),
+        None => solver.solve_with(&options),
     };
+    if let Some(err) = thrown { return Err(err); }
+
+    // explaining searches again, so it gets whatever budget is left
+    let explanation = (res.schedules.is_empty() && res.status.is_exhaustive())
+        .then(|| solver.explain_infeasible_with(&options.remaining(res.nodes, now_ms() - started)))
+        .flatten();
+    if let Some(explanation) = explanation {
+        return Err(error_value(&SolveError::NoSchedule { explanation }));
+    }
+    to_js(&res).map_err(|err| error_value(&err))
+}
+
+fn stream(solver: BTSolver, options: JsSolveOptions, cancel: &CancelFlag) -> Result<ScheduleStream, SolveError> {
+    let options = SolveOptions { cancel: Some(cancel.clone()), ..solve_options(options)? };
+    Ok(ScheduleStream { schedules: solver.into_schedules(&options) })
+}
+
+fn parse_catalog(catalog: JsCatalog) -> Result<CourseListContext, SolveError> {
+    let gql_response = GqlResponse::from_deserializer(serde_wasm_bindgen::Deserializer::from(JsValue::from(catalog)))?;
+    Ok(CourseListContext::new(gql_response))
+}
 
-    let prefs = match CoursePreferences::new(want, ctx) {
-        Ok(prefs) => prefs,
-        Err(err) => return serde_wasm_bindgen::to_value(err.msg).unwrap()
+fn build_solver(ctx: &CourseListContext, want: JsWant, schedule_prefs: JsSchedulePreferences, constraints: JsConstraints,
+    walking_times: JsWalkingTimes) -> Result<BTSolver, SolveError> {
+    let want: Want = serde_wasm_bindgen::from_value(want.into())
+        .map_err(|err| SolveError::InvalidPreferences { reason: err.to_string() })?;
+    let (courses, (min_courses, max_courses), (min_units, max_units)) = match want {
+        Want::Courses(courses) => (courses, (None, None), (None, None)),
+        Want::Selection { courses, min_courses, max_courses, min_units, max_units } =>
+            (courses, (min_courses, max_courses), (min_units, max_units)),
     };
 
-    let solver = BTSolver::new(prefs);
-    let res = solver.solve();
-    
-    serde_wasm_bindgen::to_value(&res).unwrap()
-}
\ No newline at end of file
+    // an array of { kind: "blockedDay", day: "Friday" } style objects, or nothing
+    let constraints: Option<Vec<Constraint>> = serde_wasm_bindgen::from_value(constraints.into())
+        .map_err(|err| SolveError::InvalidPreferences { reason: err.to_string() })?;
+    let walking: Option<Walking> = serde_wasm_bindgen::from_value(walking_times.into())
+        .map_err(|err| SolveError::InvalidWalkingTimes { reason: err.to_string() })?;
+    let (walking_times, strict) = match walking {
+        Some(Walking::Strict { times, strict }) => (times, strict),
+        Some(Walking::Times(times)) => (times, false),
+        None => (WalkingTimes::default(), false),
+    };
+
+    let prefs = CoursePreferences::new(courses, ctx)?
+        .with_course_count(min_courses, max_courses)
+        .with_unit_range(min_units, max_units)
+        .with_walking_times(&walking_times, strict)
+        .with_constraints(&constraints.unwrap_or_default());
+
+    // leaving the preferences out only penalizes gaps between classes
+    let schedule_prefs: Option<SchedulePreferences> = serde_wasm_bindgen::from_value(schedule_prefs.into())
+        .map_err(|err| SolveError::InvalidPreferences { reason: err.to_string() })?;
+
+    Ok(BTSolver::new(prefs).with_preferences(schedule_prefs.unwrap_or_default()))
+}
+
+fn solve_options(options: JsSolveOptions) -> Result<SolveOptions, SolveError> {
+    let options: Option<SolveOptions> = serde_wasm_bindgen::from_value(options.into())
+        .map_err(|err| SolveError::InvalidPreferences { reason: err.to_string() })?;
+    Ok(options.unwrap_or_default())
+}
+
+#[wasm_bindgen]
+pub struct ScheduleStream {
+    schedules: Schedules<BTSolver>,
+}
+
+#[wasm_bindgen]
+impl ScheduleStream {
+    // up to n more schedules, an empty array once there are none left
+    pub fn next_batch(&mut self, n: usize) -> Result<JsSchedules, JsValue> {
+        let batch: Vec<Schedule> = self.schedules.by_ref().take(n).collect();
+        to_js(&batch).map_err(|err| error_value(&err))
+    }
+
+    // { nodes, found, remaining } so far, for polling between batches
+    pub fn progress(&self) -> Result<JsProgress, JsValue> {
+        to_js(&self.schedules.progress()).map_err(|err| error_value(&err))
+    }
+
+    // "exhaustive" or why the stream stopped early, undefined while there may be more
+    pub fn status(&self) -> Result<JsSearchStatus, JsValue> {
+        match self.schedules.status() {
+            Some(status) => to_js(&status).map_err(|err| error_value(&err)),
+            None => Ok(JsValue::UNDEFINED.unchecked_into()),
+        }
+    }
+}
+
+// the typed js value for something serializable, the type is only checked by typescript
+fn to_js<T: JsCast>(value: &impl Serialize) -> Result<T, SolveError> {
+    serde_wasm_bindgen::to_value(value)
+        .map(JsCast::unchecked_into)
+        .map_err(|err| SolveError::Serialization { reason: err.to_string() })
+}
+
+fn error_value(err: &SolveError) -> JsValue {
+    serde_wasm_bindgen::to_value(&ErrorReport::from(err))
+        .unwrap_or_else(|_| JsValue::from_str(&err.to_string()))
+}
+
+// takes one schedule from solve and returns the .ics text, throws like solve
+#[wasm_bindgen]
+pub fn schedule_to_ics(schedule: JsSchedule) -> Result<String, JsValue> {
+    serde_wasm_bindgen::from_value::<Schedule>(schedule.into())
+        .map_err(|err| SolveError::InvalidSchedule { reason: err.to_string() })
+        .and_then(|schedule| ics::schedule_to_ics(&schedule.sections))
+        .map_err(|err| error_value(&err))
+}
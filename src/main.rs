@@ -1,7 +1,285 @@
-pub mod parse;
-pub mod solver;
-pub mod utils;
+use std::{env, fmt, fs, process::ExitCode};
+use std::io::{self, Write};
 
-fn main() {
-    println!("Hello, world!");
+use moogan_course_scheduler::parse::{CourseListContext, CourseRequest, WantedCourse};
+use moogan_course_scheduler::solver::{BTSolver, CoursePreferences, SolveOptions};
+use moogan_course_scheduler::utils::{now_ms, SolveError};
+use moogan_course_scheduler::walking::WalkingTimes;
+use serde_json::Value;
+
+const USAGE: &str = "usage:
+    moogan solve --catalog <file.json> --want <lecture id or course code>,... [--optional <id or code>,...]
+                 [--min-courses <n>] [--max-courses <n>] [--min-units <n>] [--max-units <n>]
+                 [--walking-times <file.json or file.csv> [--strict-walking]] [--top <k>]
+                 [--max-nodes <n>] [--time-limit <ms>]
+    moogan list --catalog <file.json>
+    moogan inspect <id> --catalog <file.json>";
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Solve {
+        catalog: String,
+        want: Vec<WantedCourse>,
+        min_courses: Option<usize>,
+        max_courses: Option<usize>,
+        min_units: Option<f64>,
+        max_units: Option<f64>,
+        walking_times: Option<String>,
+        strict_walking: bool,
+        top: Option<usize>,
+        max_nodes: Option<u64>,
+        time_limit: Option<f64>
+    },
+    List { catalog: String },
+    Inspect { catalog: String, id: u64 },
+}
+
+#[derive(Debug)]
+enum CliError {
+    Usage(String),
+    Catalog(String),
+    Solve(SolveError),
+    Output(io::Error),
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Usage(msg) => write!(f, "{}\n\n{}", msg, USAGE),
+            Self::Catalog(msg) => write!(f, "{}", msg),
+            Self::Solve(err) => write!(f, "{}", err),
+            Self::Output(err) => write!(f, "cannot write output: {}", err),
+        }
+    }
+}
+
+impl From<SolveError> for CliError {
+    fn from(value: SolveError) -> Self {
+        Self::Solve(value)
+    }
+}
+
+impl From<io::Error> for CliError {
+    fn from(value: io::Error) -> Self {
+        Self::Output(value)
+    }
+}
+
+fn parse_id(raw: &str) -> Result<u64, CliError> {
+    raw.trim().parse().map_err(|_| CliError::Usage(format!("{:?} is not a course id", raw)))
+}
+
+fn parse_count(flag: &str, raw: &str) -> Result<usize, CliError> {
+    raw.parse().map_err(|_| CliError::Usage(format!("{} needs a number", flag)))
+}
+
+fn parse_units(flag: &str, raw: &str) -> Result<f64, CliError> {
+    raw.parse().ok().filter(|units: &f64| units.is_finite() && *units >= 0.0)
+        .ok_or_else(|| CliError::Usage(format!("{} needs a number of units", flag)))
+}
+
+// numbers are lecture ids, anything else is a course code like CSE-150
+fn parse_request(raw: &str) -> Result<CourseRequest, CliError> {
+    let raw = raw.trim();
+    if raw.is_empty() { return Err(CliError::Usage("empty course in --want".into())); }
+
+    Ok(raw.parse().map_or_else(|_| CourseRequest::from(raw), CourseRequest::Lecture))
+}
+
+fn parse_args(args: &[String]) -> Result<Command, CliError> {
+    let (subcommand, rest) = args.split_first().ok_or_else(|| CliError::Usage("missing subcommand".into()))?;
+    if !matches!(subcommand.as_str(), "solve" | "list" | "inspect") {
+        return Err(CliError::Usage(format!("unknown subcommand {}", subcommand)));
+    }
+
+    let mut catalog = None;
+    let mut want = None;
+    let mut optional = Vec::new();
+    let mut min_courses = None;
+    let mut max_courses = None;
+    let mut min_units = None;
+    let mut max_units = None;
+    let mut walking_times = None;
+    let mut strict_walking = false;
+    let mut top = None;
+    let mut max_nodes = None;
+    let mut time_limit = None;
+    let mut positional = Vec::new();
+
+    let mut rest = rest.iter();
+    while let Some(arg) = rest.next() {
+        let mut value = || rest.next().ok_or_else(|| CliError::Usage(format!("{} needs a value", arg)));
+        match arg.as_str() {
+            "--catalog" => catalog = Some(value()?.clone()),
+            "--want" => want = Some(value()?.split(',').map(parse_request).collect::<Result<Vec<_>, _>>()?),
+            "--optional" => for raw in value()?.split(',') {
+                optional.push(parse_request(raw)?.optional());
+            },
+            "--min-courses" => min_courses = Some(parse_count(arg, value()?)?),
+            "--max-courses" => max_courses = Some(parse_count(arg, value()?)?),
+            "--min-units" => min_units = Some(parse_units(arg, value()?)?),
+            "--max-units" => max_units = Some(parse_units(arg, value()?)?),
+            "--walking-times" => walking_times = Some(value()?.clone()),
+            "--strict-walking" => strict_walking = true,
+            "--top" => top = Some(parse_count(arg, value()?)?),
+            "--max-nodes" => max_nodes = Some(parse_count(arg, value()?)? as u64),
+            "--time-limit" => time_limit = Some(parse_count(arg, value()?)? as f64),
+            flag if flag.starts_with("--") => return Err(CliError::Usage(format!("unknown option {}", flag))),
+            _ => positional.push(arg),
+        }
+    }
+
+    let catalog = catalog.ok_or_else(|| CliError::Usage("--catalog is required".into()))?;
+    match (subcommand.as_str(), positional.as_slice()) {
+        ("solve", []) => {
+            let want: Vec<CourseRequest> = want.ok_or_else(|| CliError::Usage("--want is required".into()))?;
+            let want = want.into_iter().map(WantedCourse::from).chain(optional).collect();
+            if strict_walking && walking_times.is_none() {
+                return Err(CliError::Usage("--strict-walking needs --walking-times".into()));
+            }
+            Ok(Command::Solve {
+                catalog, want, min_courses, max_courses, min_units, max_units, walking_times, strict_walking, top, max_nodes, time_limit
+            })
+        }
+        ("list", []) => Ok(Command::List { catalog }),
+        ("inspect", [id]) => Ok(Command::Inspect { catalog, id: parse_id(id)? }),
+        _ => Err(CliError::Usage(format!("wrong arguments for {}", subcommand))),
+    }
+}
+
+// accepts the raw graphql response or just its data object
+fn load_catalog(path: &str) -> Result<CourseListContext, CliError> {
+    let raw = fs::read_to_string(path).map_err(|err| CliError::Catalog(format!("cannot read {}: {}", path, err)))?;
+    let mut gql_response: Value = serde_json::from_str(&raw)
+        .map_err(|err| CliError::Catalog(format!("{} is not json: {}", path, err)))?;
+
+    if let Some(data) = gql_response.get_mut("data") {
+        gql_response = data.take();
+    }
+
+    Ok(CourseListContext::from_value(&gql_response)?)
+}
+
+fn load_walking_times(path: &str) -> Result<WalkingTimes, CliError> {
+    let raw = fs::read_to_string(path).map_err(|err| CliError::Catalog(format!("cannot read {}: {}", path, err)))?;
+    match path.to_ascii_lowercase().ends_with(".csv") {
+        true => Ok(WalkingTimes::from_csv(&raw)?),
+        false => Ok(WalkingTimes::from_json(&raw)?),
+    }
+}
+
+fn run(command: Command) -> Result<(), CliError> {
+    let mut out = io::stdout().lock();
+
+    match command {
+        Command::Solve { catalog, want, min_courses, max_courses, min_units, max_units, walking_times, strict_walking, top, max_nodes, time_limit } => {
+            let walking_times = walking_times.as_deref().map(load_walking_times).transpose()?.unwrap_or_default();
+
+            let prefs = CoursePreferences::new(want, load_catalog(&catalog)?)?
+                .with_course_count(min_courses, max_courses)
+                .with_unit_range(min_units, max_units)
+                .with_walking_times(&walking_times, strict_walking);
+            let solver = BTSolver::new(prefs);
+            let options = SolveOptions { max_nodes, deadline: time_limit, ..Default::default() };
+            let started = now_ms();
+            let res = match top {
+                Some(k) => solver.solve_top_k_with(k, &options),
+                None => solver.solve_with(&options),
+            };
+            let schedules = res.schedules;
+
+            for schedule in &schedules {
+                for meeting in &schedule.sections {
+                    writeln!(out, "{:?}", meeting)?;
+                }
+                writeln!(out, "lectures {:?}, {} units, score {:.2}", schedule.lectures, schedule.units, schedule.score)?;
+                writeln!(out)?;
+            }
+            writeln!(out, "{} schedules", schedules.len())?;
+            if !res.status.is_exhaustive() {
+                writeln!(out, "{} after {} nodes, there may be more", res.status, res.nodes)?;
+            } else if schedules.is_empty() {
+                // with whatever is left of the limits, it searches again
+                match solver.explain_infeasible_with(&options.remaining(res.nodes, now_ms() - started)) {
+                    Some(explanation) => writeln!(out, "{}", explanation)?,
+                    None => writeln!(out, "ran out of time or nodes working out why")?,
+                }
+            }
+        }
+        Command::List { catalog } => {
+            let ctx = load_catalog(&catalog)?;
+            let mut courses: Vec<_> = ctx.id_to_course.values().collect();
+            courses.sort_by(|a, b| a.course_number.cmp(&b.course_number));
+
+            for course in courses {
+                writeln!(out, "{}  {}", course.id, course.course_number)?;
+            }
+        }
+        Command::Inspect { catalog, id } => {
+            let ctx = load_catalog(&catalog)?;
+            for meeting in ctx.meetings_from_lectures(&[id])? {
+                writeln!(out, "{:?}", meeting)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    match parse_args(&args).and_then(run) {
+        Ok(()) => ExitCode::SUCCESS,
+        // piped into head or similar
+        Err(CliError::Output(err)) if err.kind() == io::ErrorKind::BrokenPipe => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            match err {
+                CliError::Usage(_) => ExitCode::from(2),
+                _ => ExitCode::FAILURE,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use moogan_course_scheduler::parse::CourseRequest;
+    use crate::{parse_args, CliError, Command};
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn parses_subcommands() {
+        assert_eq!(parse_args(&args("solve --catalog mess.json --want 2023337427,2023337795")).unwrap(),
+            Command::Solve {
+                catalog: "mess.json".into(), want: vec![2023337427u64.into(), 2023337795u64.into()],
+                min_courses: None, max_courses: None, min_units: None, max_units: None,
+                walking_times: None, strict_walking: false, top: None, max_nodes: None, time_limit: None
+            });
+        assert_eq!(parse_args(&args("solve --catalog mess.json --want CSE-150 --optional ANTH-1,2023337795 --max-courses 2 --min-units 12 --walking-times walk.csv --strict-walking --top 3 --max-nodes 5000 --time-limit 250")).unwrap(),
+            Command::Solve {
+                catalog: "mess.json".into(),
+                want: vec!["CSE-150".into(), CourseRequest::from("ANTH-1").optional(), CourseRequest::from(2023337795).optional()],
+                min_courses: None, max_courses: Some(2), min_units: Some(12.0), max_units: None,
+                walking_times: Some("walk.csv".into()), strict_walking: true, top: Some(3), max_nodes: Some(5000), time_limit: Some(250.0)
+            });
+        assert_eq!(parse_args(&args("inspect 2023337427 --catalog mess.json")).unwrap(),
+            Command::Inspect { catalog: "mess.json".into(), id: 2023337427 });
+        assert_eq!(parse_args(&args("list --catalog mess.json")).unwrap(), Command::List { catalog: "mess.json".into() });
+    }
+
+    #[test]
+    fn rejects_bad_usage() {
+        for line in ["", "bogus --catalog mess.json", "solve --catalog mess.json", "list", "inspect abc --catalog mess.json", "solve --want 1 --catalog", "solve --catalog mess.json --want 1,,2",
+            "solve --catalog mess.json --want 1 --min-courses x",
+            "solve --catalog mess.json --want 1 --max-units -4",
+            "solve --catalog mess.json --want 1 --strict-walking",
+            "solve --catalog mess.json --want 1 --time-limit soon"] {
+            assert!(matches!(parse_args(&args(line)), Err(CliError::Usage(_))), "{:?}", line);
+        }
+    }
 }
@@ -0,0 +1,116 @@
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+use crate::utils::SolveError;
+
+// typed view of the `data` object returned by the course api, field names follow the graphql schema
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GqlResponse {
+    pub meeting_types: Vec<MeetingTypeEntry>,
+    pub classes: ClassConnection,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ClassConnection {
+    pub nodes: Vec<ClassNode>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MeetingTypeEntry {
+    pub id: u64,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClassNode {
+    pub id: u64,
+    // subject, number and section, e.g. CSE-150-01 or CSE-150-03L
+    pub course_number: String,
+    // credit units, usually only set on lectures
+    #[serde(default)]
+    pub units: Option<f64>,
+    // seats taken, seats in total and students waiting, left out by some catalogs
+    #[serde(default)]
+    pub enrollment: Option<u64>,
+    #[serde(default)]
+    pub capacity: Option<u64>,
+    #[serde(default)]
+    pub waitlist: Option<u64>,
+    // names as the registrar lists them, empty when not yet assigned
+    #[serde(default)]
+    pub instructors: Vec<String>,
+    pub linked_sections: Vec<LinkedSection>,
+    pub meetings: Vec<Meeting>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkedSection {
+    pub parent: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Meeting {
+    // bit i set means the meeting happens on day i
+    pub in_session: u64,
+    pub meeting_type: u64,
+    // "1330" style, missing for sections without a set time
+    pub begin_time: Option<String>,
+    pub end_time: Option<String>,
+    // first and last day the meeting happens, a single day for exams
+    pub begin_date: Option<String>,
+    pub end_date: Option<String>,
+    // where it happens, missing for online and unassigned meetings
+    #[serde(default)]
+    pub building: Option<String>,
+    #[serde(default)]
+    pub room: Option<String>,
+}
+
+impl ClassNode {
+    // CSE-150-03L -> CSE-150
+    pub fn course_code(&self) -> &str {
+        self.course_number.rsplit_once('-').map_or(&self.course_number, |(code, _)| code)
+    }
+}
+
+impl GqlResponse {
+    pub fn from_value(gql_response_json: &Value) -> Result<Self, SolveError> {
+        Self::from_deserializer(gql_response_json)
+    }
+
+    // errors name the offending field, e.g. classes.nodes[3].meetings[0].inSession
+    pub fn from_deserializer<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, SolveError>
+    where D::Error: std::fmt::Display {
+        serde_path_to_error::deserialize(deserializer)
+            .map_err(|err| SolveError::InvalidCatalog { path: err.path().to_string(), reason: err.inner().to_string() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::model::GqlResponse;
+    use crate::utils::SolveError;
+
+    #[test]
+    fn error_names_field_path() {
+        let gql_response = json!({
+            "meetingTypes": [{ "id": 1, "name": "Lecture" }],
+            "classes": { "nodes": [{
+                "id": 1,
+                "courseNumber": "CSE-150-01",
+                "linkedSections": [],
+                "meetings": [{ "inSession": "MW", "meetingType": 1, "beginTime": "1030", "endTime": "1145" }]
+            }] }
+        });
+
+        match GqlResponse::from_value(&gql_response) {
+            Err(SolveError::InvalidCatalog { path, .. }) => assert_eq!(path, "classes.nodes[0].meetings[0].inSession"),
+            other => panic!("expected InvalidCatalog, got {:?}", other)
+        }
+    }
+}
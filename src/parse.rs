@@ -1,11 +1,15 @@
-use std::{str, collections::HashMap, fmt::Debug};
+use std::{str, collections::{HashMap, HashSet}, fmt::Debug};
 
+use log::warn;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use crate::calendar::{Date, Weekday};
+use crate::model::{ClassNode, GqlResponse, Meeting};
 use crate::utils::SolveError;
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub enum MeetingType {
+    #[default]
     Lecture, // discussion, lab. some lectures need both lab and discussion
     Discussion,
     Lab,
@@ -13,20 +17,131 @@ pub enum MeetingType {
     Other
 }
 
+const DAY: u64 = 24 * 3600;
+
+// what a student asks for, one exact lecture section or any lecture section of a course
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum CourseRequest {
+    Lecture(u64),
+    // subject and number, e.g. CSE-150
+    Course(String),
+}
+
+// a request and whether the schedule may leave it out. plain ids and codes are required,
+// { "course": "CSE-20", "optional": true } is not
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "WantedCourseRepr")]
+pub struct WantedCourse {
+    pub course: CourseRequest,
+    pub optional: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum WantedCourseRepr {
+    Plain(CourseRequest),
+    Flagged { course: CourseRequest, #[serde(default)] optional: bool },
+}
+
 #[derive(Debug)]
-pub struct CourseListContext<'a> {
+pub struct CourseListContext {
     id_to_meet_string: HashMap<u64, String>,
-    pub id_to_course: HashMap<u64, &'a Value>
+    pub id_to_course: HashMap<u64, ClassNode>,
+    // linkedSections in both directions, section id -> every section linked to it
+    links: HashMap<u64, HashSet<u64>>
 }
 
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, Default, Serialize, Deserialize)]
 pub struct SectionMeeting {
+    // seconds since sunday 12am of the meeting's week
     pub u_start: u64,
     pub u_end: u64,
     pub section_id: u64,
     pub lecture_id: u64,
     pub section_name: String,
-    pub meeting_type: MeetingType
+    pub meeting_type: MeetingType,
+    // first and last day of the meeting, the same day for exams
+    pub start_date: Option<Date>,
+    pub end_date: Option<Date>,
+    // seats left and students waiting in the section, None when the catalog doesn't say
+    #[serde(default)]
+    pub open_seats: Option<u64>,
+    #[serde(default)]
+    pub waitlist: Option<u64>,
+    #[serde(default)]
+    pub instructors: Vec<String>,
+    #[serde(default)]
+    pub building: Option<String>,
+    #[serde(default)]
+    pub room: Option<String>
+}
+
+impl From<u64> for CourseRequest {
+    fn from(value: u64) -> Self {
+        Self::Lecture(value)
+    }
+}
+
+impl From<&str> for CourseRequest {
+    fn from(value: &str) -> Self {
+        Self::Course(value.into())
+    }
+}
+
+impl CourseRequest {
+    pub fn optional(self) -> WantedCourse {
+        WantedCourse { course: self, optional: true }
+    }
+}
+
+impl<R: Into<CourseRequest>> From<R> for WantedCourse {
+    fn from(value: R) -> Self {
+        Self { course: value.into(), optional: false }
+    }
+}
+
+impl From<WantedCourseRepr> for WantedCourse {
+    fn from(value: WantedCourseRepr) -> Self {
+        match value {
+            WantedCourseRepr::Plain(course) => course.into(),
+            WantedCourseRepr::Flagged { course, optional } => Self { course, optional },
+        }
+    }
+}
+
+// js hands over numbers as floats, so this takes any integral number or a course code
+impl<'de> Deserialize<'de> for CourseRequest {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct RequestVisitor;
+
+        impl serde::de::Visitor<'_> for RequestVisitor {
+            type Value = CourseRequest;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(f, "a lecture id or a course code like CSE-150")
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<Self::Value, E> {
+                Ok(CourseRequest::Lecture(v))
+            }
+
+            fn visit_i64<E: serde::de::Error>(self, v: i64) -> Result<Self::Value, E> {
+                u64::try_from(v).map(CourseRequest::Lecture).map_err(|_| E::custom("negative lecture id"))
+            }
+
+            fn visit_f64<E: serde::de::Error>(self, v: f64) -> Result<Self::Value, E> {
+                if v.fract() != 0.0 || v < 0.0 { return Err(E::custom("lecture ids are whole numbers")); }
+                Ok(CourseRequest::Lecture(v as u64))
+            }
+
+            fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                Ok(CourseRequest::Course(v.into()))
+            }
+        }
+
+        deserializer.deserialize_any(RequestVisitor)
+    }
 }
 
 impl From<&str> for MeetingType {
@@ -41,126 +156,256 @@ impl From<&str> for MeetingType {
     }
 }
 
-fn day_to_str(day: u64) -> &'static str{
-    return match day {
-        0 => "SAT",
-        1 => "MON",
-        2 => "TUE",
-        3 => "WED",
-        4 => "THR",
-        5 => "FRI",
-        6 => "SUN",
-        7 => "EX_SAT",
-        8 => "EX_MON",
-        9 => "EX_TUE",
-        10 => "EX_WED",
-        11 => "EX_THR",
-        12 => "EX_FRI",
-        13 => "EX_SUN",
-        _ => "INVALID"
-    }
-}
-
 fn hour_to_murican(u_time: u64) -> String {
     let hour = u_time % (24 * 3600) / 3600;
     let am_or_pm = if hour >= 12 { "PM" } else { "AM" };
-    let hour = if hour == 0 { 12 } else { hour };
+    let hour = match hour % 12 { 0 => 12, hour => hour };
     let minute =  u_time % (24 * 3600) % 3600 / 60;
-    return format!("{}:{:0>2} {}", hour, minute, am_or_pm);
+    format!("{}:{:0>2} {}", hour, minute, am_or_pm)
+}
+
+impl SectionMeeting {
+    // e.g. CSE-150-01 Lecture TUE 1:30 PM-2:45 PM, for messages meant for people
+    pub fn describe(&self) -> String {
+        let day = match (self.is_exam(), self.start_date) {
+            (true, Some(date)) => date.to_string(),
+            _ => self.weekday().abbrev().to_string()
+        };
+        format!("{} {:?} {} {}-{}", self.section_name, self.meeting_type, day,
+            hour_to_murican(self.u_start), hour_to_murican(self.u_end))
+    }
 }
 
 impl Debug for SectionMeeting {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let start_day = self.u_start / (24 * 3600);
-
-        let end_day = self.u_end / (24 * 3600);
+        // exams are one-offs, so show which day rather than just the weekday
+        let day = match (self.is_exam(), self.start_date) {
+            (true, Some(date)) => format!("{} {}", self.weekday().abbrev(), date),
+            _ => self.weekday().abbrev().to_string()
+        };
 
         write!(f, "{} {{ start: {} {}, end: {} {}, type: {:?} }}", self.section_name, 
-            day_to_str(start_day), hour_to_murican(self.u_start),
-            day_to_str(end_day), hour_to_murican(self.u_end), 
+            day, hour_to_murican(self.u_start),
+            day, hour_to_murican(self.u_end), 
             self.meeting_type)
     }
 }
 
+impl SectionMeeting {
+    pub fn weekday(&self) -> Weekday {
+        Weekday::from_index(self.u_start / DAY).unwrap_or(Weekday::Sunday)
+    }
 
-impl<'a> CourseListContext<'a> {
-    pub fn new(gql_response_json: &'a Value) -> Result<Self, SolveError> {
-        let mut id_to_meet_string = HashMap::new();
-        let mut id_to_course: HashMap<u64, &'a Value> = HashMap::new();
+    // names compare ignoring case and surrounding spaces
+    pub fn taught_by_any(&self, names: &[String]) -> bool {
+        self.instructors.iter().any(|instructor| {
+            names.iter().any(|name| instructor.trim().eq_ignore_ascii_case(name.trim()))
+        })
+    }
 
-        gql_response_json["meetingTypes"].as_array()
-            .ok_or("invalid meetingTypes")?
-            .iter().try_for_each(|val| -> Result<(), SolveError> {
-                id_to_meet_string.insert(val["id"].as_u64().ok_or("invalid id")?, val["name"].as_str().ok_or("invalid name")?.into());
-                Ok(())
-            })?;
+    pub fn is_full(&self) -> bool {
+        self.open_seats == Some(0)
+    }
 
-        gql_response_json["classes"]["nodes"].as_array()
-            .ok_or("invalid nodes")?
-            .iter().try_for_each(|val| -> Result<(), SolveError> {
-                id_to_course.insert(val["id"].as_u64().ok_or("invalid id")?, val);
-                Ok(())
-            })?;
+    pub fn is_exam(&self) -> bool {
+        matches!(self.meeting_type, MeetingType::Exam)
+    }
+
+    // exams are dated one-off events, so they only clash with exams on the same day.
+    // weekly meetings only clash with weekly meetings. touching counts as a clash
+    pub fn conflicts_with(&self, other: &SectionMeeting) -> bool {
+        if self.is_exam() != other.is_exam() { return false; }
+        if let (true, Some(date), Some(other_date)) = (self.is_exam(), self.start_date, other.start_date) {
+            if date != other_date { return false; }
+        }
+
+        self.u_start <= other.u_end && other.u_start <= self.u_end
+    }
+}
+
+
+// "1130" into seconds after midnight, 11 hours 30 mins
+fn parse_time(section_id: u64, field: &'static str, raw: Option<&str>) -> Result<u64, SolveError> {
+    let bad_time = || SolveError::BadTime { section_id, field, raw: raw.map(String::from) };
+
+    let digits = raw.ok_or_else(bad_time)?.parse::<u64>().map_err(|_| bad_time())?;
+    if digits > 2400 || digits % 100 >= 60 {
+        return Err(bad_time());
+    }
+
+    Ok((digits / 100) * 3600 + (digits % 100) * 60)
+}
+
+// weekly meetings happen on their inSession days. an exam happens on the weekday of its date,
+// the bits only count for exams without one
+fn meeting_days(section_id: u64, meeting: &Meeting, meeting_type: MeetingType, start_date: Option<Date>) -> Vec<Weekday> {
+    if meeting.in_session >= 1 << 7 {
+        warn!("section {} meets on unknown days {:#b}", section_id, meeting.in_session);
+    }
+    let days = Weekday::from_bits(meeting.in_session);
+
+    match (meeting_type, start_date) {
+        (MeetingType::Exam, Some(date)) => {
+            if !days.is_empty() && days != [date.weekday()] {
+                warn!("section {} has an exam on {} but inSession says {:?}", section_id, date, days);
+            }
+            vec![date.weekday()]
+        }
+        _ => days,
+    }
+}
 
-        Ok(Self {
+fn parse_date(section_id: u64, field: &'static str, raw: Option<&str>) -> Result<Option<Date>, SolveError> {
+    raw.map(|raw| Date::parse(raw).ok_or_else(|| SolveError::BadDate { section_id, field, raw: raw.into() }))
+        .transpose()
+}
+
+impl CourseListContext {
+    pub fn new(gql_response: GqlResponse) -> Self {
+        let id_to_meet_string = gql_response.meeting_types.into_iter()
+            .map(|meeting_type| (meeting_type.id, meeting_type.name))
+            .collect();
+
+        let id_to_course: HashMap<u64, ClassNode> = gql_response.classes.nodes.into_iter()
+            .map(|course| (course.id, course))
+            .collect();
+
+        let mut links: HashMap<u64, HashSet<u64>> = HashMap::new();
+        for course in id_to_course.values() {
+            for linked_section in &course.linked_sections {
+                if linked_section.parent == course.id { continue; }
+                links.entry(course.id).or_default().insert(linked_section.parent);
+                links.entry(linked_section.parent).or_default().insert(course.id);
+            }
+        }
+
+        Self {
             id_to_meet_string,
             id_to_course,
-        })
+            links,
+        }
+    }
+
+    pub fn linked(&self, section_id: u64, other_id: u64) -> bool {
+        self.links.get(&section_id).is_some_and(|linked| linked.contains(&other_id))
+    }
+
+    // whether a lab and a discussion of the same lecture may be taken together. sections
+    // linked to some of the other kind only go with those, unlinked ones go with anything
+    pub fn may_pair(&self, lab_id: u64, discussion_id: u64, lab_ids: &[u64], discussion_ids: &[u64]) -> bool {
+        if self.linked(lab_id, discussion_id) { return true; }
+
+        let lab_is_paired = discussion_ids.iter().any(|&id| self.linked(lab_id, id));
+        let discussion_is_paired = lab_ids.iter().any(|&id| self.linked(discussion_id, id));
+        !lab_is_paired && !discussion_is_paired
+    }
+
+    pub fn from_value(gql_response_json: &Value) -> Result<Self, SolveError> {
+        Ok(Self::new(GqlResponse::from_value(gql_response_json)?))
+    }
+
+    // lecture section ids that can satisfy the request, sorted by id
+    pub fn lecture_options(&self, request: &CourseRequest) -> Result<Vec<u64>, SolveError> {
+        match request {
+            CourseRequest::Lecture(id) => match self.id_to_course.contains_key(id) {
+                true => Ok(vec![*id]),
+                false => Err(SolveError::MissingCourse { id: *id }),
+            },
+            CourseRequest::Course(code) => {
+                let mut lecture_ids: Vec<u64> = self.id_to_course.values()
+                    .filter(|course| course.course_code().eq_ignore_ascii_case(code.trim()) && self.is_lecture(course))
+                    .map(|course| course.id)
+                    .collect();
+                lecture_ids.sort();
+
+                if lecture_ids.is_empty() {
+                    return Err(SolveError::UnknownCourse { code: code.clone() });
+                }
+                Ok(lecture_ids)
+            }
+        }
+    }
+
+    // lecture sections whose course number contains query, ignoring case and punctuation so
+    // "cse150" finds CSE-150-01. sorted by course number
+    pub fn search(&self, query: &str) -> Vec<&ClassNode> {
+        let squash = |text: &str| -> String {
+            text.chars().filter(char::is_ascii_alphanumeric).map(|c| c.to_ascii_uppercase()).collect()
+        };
+        let query = squash(query);
+        if query.is_empty() { return Vec::new(); }
+
+        let mut found: Vec<&ClassNode> = self.id_to_course.values()
+            .filter(|course| squash(&course.course_number).contains(&query) && self.is_lecture(course))
+            .collect();
+        found.sort_by(|a, b| a.course_number.cmp(&b.course_number).then(a.id.cmp(&b.id)));
+        found
+    }
+
+    // sections with lecture meetings, and ones like online lectures that have no meetings of
+    // their own as long as they aren't a lab or discussion of some lecture
+    fn is_lecture(&self, course: &ClassNode) -> bool {
+        if self.has_meeting(course, MeetingType::Lecture) { return true; }
+        if self.has_meeting(course, MeetingType::Lab) || self.has_meeting(course, MeetingType::Discussion) { return false; }
+
+        !self.links.get(&course.id).is_some_and(|linked| linked.iter().any(|id| {
+            self.id_to_course.get(id).is_some_and(|other| self.has_meeting(other, MeetingType::Lecture))
+        }))
+    }
+
+    fn has_meeting(&self, course: &ClassNode, meeting_type: MeetingType) -> bool {
+        course.meetings.iter().any(|meeting| self.meeting_type_from_id(meeting.meeting_type) == Ok(meeting_type))
     }
 
     // generates list of meeting times (of lectures, labs, discussions) from lecture sessions
-    pub fn meetings_from_lectures(&self, lecture_ids: &Vec<u64>) -> Result<Vec<SectionMeeting>, SolveError> {
+    pub fn meetings_from_lectures(&self, lecture_ids: &[u64]) -> Result<Vec<SectionMeeting>, SolveError> {
         let mut meetings = Vec::new();
 
-        lecture_ids.iter().try_for_each(|lecture_id| -> Result<(), SolveError> {
+        lecture_ids.iter().try_for_each(|&lecture_id| -> Result<(), SolveError> {
+            let lecture = self.id_to_course.get(&lecture_id).ok_or(SolveError::MissingCourse { id: lecture_id })?;
             // to cover the main lecture section itself
-            self.push_meetings(&mut meetings, *lecture_id, *lecture_id)?;
-            // to cover each of its labs/discussion whatever
-            self.id_to_course.get(&lecture_id).ok_or("lecture id is not in id_to_course")?["linkedSections"].as_array()
-                .ok_or("invalid linkedSections")?
-                .iter().try_for_each(|linked_section| -> Result<(), SolveError> {
-                    let section_id = linked_section["parent"].as_u64().ok_or("section id doesn't have linked parent")?;
-                    self.push_meetings(&mut meetings, *lecture_id, section_id)?;
-
-                    Ok(())
-                })?;
+            self.push_meetings(&mut meetings, lecture_id, lecture)?;
+            // to cover each of its labs/discussion whatever, once even if the catalog lists it twice
+            let mut seen = Vec::new();
+            lecture.linked_sections.iter().try_for_each(|linked_section| -> Result<(), SolveError> {
+                let section_id = linked_section.parent;
+                if seen.contains(&section_id) { return Ok(()); }
+                seen.push(section_id);
+
+                let section = self.id_to_course.get(&section_id)
+                    .ok_or(SolveError::MissingSection { lecture_id, section_id })?;
+                self.push_meetings(&mut meetings, lecture_id, section)?;
+
+                Ok(())
+            })?;
             Ok(())
         })?;
 
         Ok(meetings)
     }
 
-    fn push_meetings(&self, meetings: &mut Vec<SectionMeeting>, lecture_id: u64, section_id: u64) -> Result<(), SolveError>{
-        self.id_to_course.get(&section_id).ok_or("section id is not in id_to_course")?["meetings"]
-        .as_array().ok_or("meetings not in id_to_course[sectiond_id]")?
-        .iter().try_for_each(|meeting_value| -> Result<(), SolveError> {
-            let weekday_bits = meeting_value["inSession"].as_u64().ok_or("invalid inSession")?;
-            let meeting_id = meeting_value["meetingType"].as_u64().ok_or("invalid meetingType")?;
-            let hour_start = meeting_value["beginTime"].as_str().ok_or("invalid beginTime")?;
-            let hour_end = meeting_value["endTime"].as_str().ok_or("invalid endTime")?;
-
-            let meeting_type = self.meeting_type_from_id(meeting_id);
-
-            for i in 0..8 {
-                if weekday_bits & (1 << i) > 0 {
-                    // convert hours to uint
-                    let start_digits = hour_start.parse::<u64>().ok().ok_or("cannot parse start_digits")?;
-                    let end_digits = hour_end.parse::<u64>().ok().ok_or("cannot parse end_digits")?;
-                    // 1130 split into 11 hours 30 mins
-                    let mut u_start = i * 24 * 3600 + (start_digits / 100) * 3600 + (start_digits % 100)*60;
-                    let mut u_end = i * 24 * 3600 + (end_digits / 100) * 3600 + (end_digits % 100)*60;
-
-                    // make final exams "next week"
-                    if matches!(meeting_type, MeetingType::Exam) {
-                        u_start += 7 * 24 * 3600;
-                        u_end += 7 * 24 * 3600;
-                    }
-
-                    meetings.push(SectionMeeting { u_start: u_start, u_end: u_end, 
-                        section_id: section_id, lecture_id: lecture_id, meeting_type: meeting_type, 
-                        section_name: self.id_to_course.get(&section_id)
-                            .ok_or("section id not in id_to_course")?["courseNumber"].to_string() })
-                }
+    fn push_meetings(&self, meetings: &mut Vec<SectionMeeting>, lecture_id: u64, section: &ClassNode) -> Result<(), SolveError>{
+        let section_id = section.id;
+        let open_seats = section.capacity.map(|capacity| capacity.saturating_sub(section.enrollment.unwrap_or(0)));
+
+        section.meetings.iter().try_for_each(|meeting| -> Result<(), SolveError> {
+            let start = parse_time(section_id, "beginTime", meeting.begin_time.as_deref())?;
+            let end = parse_time(section_id, "endTime", meeting.end_time.as_deref())?;
+            let start_date = parse_date(section_id, "beginDate", meeting.begin_date.as_deref())?;
+            let end_date = parse_date(section_id, "endDate", meeting.end_date.as_deref())?;
+
+            let meeting_type = self.meeting_type_from_id(meeting.meeting_type)?;
+
+            for day in meeting_days(section_id, meeting, meeting_type, start_date) {
+                meetings.push(SectionMeeting { 
+                    u_start: day.index() * DAY + start,
+                    u_end: day.index() * DAY + end,
+                    section_id, lecture_id, meeting_type, 
+                    section_name: section.course_number.clone(),
+                    start_date, end_date,
+                    open_seats, waitlist: section.waitlist,
+                    instructors: section.instructors.clone(),
+                    building: meeting.building.clone(), room: meeting.room.clone() })
             }
             Ok(())
         })?;
@@ -168,26 +413,172 @@ impl<'a> CourseListContext<'a> {
         Ok(())
     }
 
-    fn meeting_type_from_id(&self, id: u64) -> MeetingType{
-        let meet_string = &*self.id_to_meet_string[&id];
-        MeetingType::from(meet_string)
+    fn meeting_type_from_id(&self, id: u64) -> Result<MeetingType, SolveError> {
+        let meet_string = self.id_to_meet_string.get(&id).ok_or(SolveError::UnknownMeetingType { id })?;
+        Ok(MeetingType::from(meet_string.as_str()))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use std::fs;
-    use serde_json::Value;
-    use crate::parse::CourseListContext;
+    use serde_json::{json, Value};
+    use crate::calendar::{Date, Weekday};
+    use crate::parse::{CourseListContext, CourseRequest, MeetingType, WantedCourse};
+    use crate::utils::SolveError;
+
+    fn lecture_with_lab(lab_begin: Value) -> Value {
+        json!({
+            "meetingTypes": [{ "id": 1, "name": "Lecture" }, { "id": 2, "name": "Lab" }],
+            "classes": { "nodes": [
+                { "id": 1, "courseNumber": "CSE-150-01", "linkedSections": [{ "parent": 2 }, { "parent": 3 }],
+                    "meetings": [{ "inSession": 2, "meetingType": 1, "beginTime": "1030", "endTime": "1145" }] },
+                { "id": 2, "courseNumber": "CSE-150-02L", "linkedSections": [],
+                    "meetings": [{ "inSession": 4, "meetingType": 2, "beginTime": lab_begin, "endTime": "1650" }] }
+            ] }
+        })
+    }
+
+    #[test]
+    fn errors_carry_ids() {
+        let ctx = CourseListContext::from_value(&lecture_with_lab(json!("1400"))).unwrap();
+        assert_eq!(ctx.meetings_from_lectures(&[7]).unwrap_err(), SolveError::MissingCourse { id: 7 });
+        assert_eq!(ctx.meetings_from_lectures(&[1]).unwrap_err(), SolveError::MissingSection { lecture_id: 1, section_id: 3 });
+
+        let ctx = CourseListContext::from_value(&lecture_with_lab(json!("14:00"))).unwrap();
+        assert_eq!(ctx.meetings_from_lectures(&[1]).unwrap_err(),
+            SolveError::BadTime { section_id: 2, field: "beginTime", raw: Some("14:00".into()) });
+
+        let ctx = CourseListContext::from_value(&lecture_with_lab(Value::Null)).unwrap();
+        assert_eq!(ctx.meetings_from_lectures(&[1]).unwrap_err().to_string(), "section 2 has no beginTime");
+    }
+
+    #[test]
+    fn course_codes_resolve_to_lectures() {
+        let mut gql_response = lecture_with_lab(json!("1400"));
+        gql_response["classes"]["nodes"].as_array_mut().unwrap().push(json!(
+            { "id": 4, "courseNumber": "CSE-150-04", "linkedSections": [{ "parent": 2 }, { "parent": 2 }],
+                "meetings": [{ "inSession": 8, "meetingType": 1, "beginTime": "1030", "endTime": "1145" }] }
+        ));
+        let ctx = CourseListContext::from_value(&gql_response).unwrap();
+
+        // the lab shares the course code but isn't a lecture
+        assert_eq!(ctx.lecture_options(&"cse-150".into()), Ok(vec![1, 4]));
+        assert_eq!(ctx.lecture_options(&4.into()), Ok(vec![4]));
+        assert_eq!(ctx.lecture_options(&"CSE-999".into()), Err(SolveError::UnknownCourse { code: "CSE-999".into() }));
+        // a section linked twice is only added once
+        assert_eq!(ctx.meetings_from_lectures(&[4]).unwrap().len(), 2);
+
+        let found: Vec<u64> = ctx.search(" cse150").iter().map(|course| course.id).collect();
+        assert_eq!(found, vec![1, 4]);
+        assert!(ctx.search("CSE-175").is_empty());
+        assert!(ctx.search("-").is_empty());
+
+        // an online lecture has no meetings to tell by, its lab does
+        let nodes = gql_response["classes"]["nodes"].as_array_mut().unwrap();
+        nodes.push(json!({ "id": 6, "courseNumber": "CSE-5-01", "linkedSections": [{ "parent": 7 }], "meetings": [] }));
+        nodes.push(json!({ "id": 7, "courseNumber": "CSE-5-02L", "linkedSections": [],
+            "meetings": [{ "inSession": 4, "meetingType": 2, "beginTime": "1300", "endTime": "1550" }] }));
+        nodes.push(json!({ "id": 8, "courseNumber": "CSE-150-05L", "linkedSections": [{ "parent": 1 }], "meetings": [] }));
+        let ctx = CourseListContext::from_value(&gql_response).unwrap();
+        assert_eq!(ctx.lecture_options(&"CSE-5".into()), Ok(vec![6]));
+        assert_eq!(ctx.lecture_options(&"CSE-150".into()), Ok(vec![1, 4]));
+        let found: Vec<u64> = ctx.search("cse 5").iter().map(|course| course.id).collect();
+        assert_eq!(found, vec![6]);
+
+        let want: Vec<CourseRequest> = serde_json::from_value(json!([4, "CSE-150"])).unwrap();
+        assert_eq!(want, vec![CourseRequest::Lecture(4), CourseRequest::Course("CSE-150".into())]);
+        assert!(serde_json::from_value::<CourseRequest>(json!(-1)).is_err());
+
+        let want: Vec<WantedCourse> = serde_json::from_value(json!([4, { "course": "CSE-150", "optional": true }])).unwrap();
+        assert_eq!(want, vec![4.into(), CourseRequest::from("CSE-150").optional()]);
+    }
+
+    #[test]
+    fn open_seats_from_capacity() {
+        let mut gql_response = lecture_with_lab(json!("1400"));
+        gql_response["classes"]["nodes"][0]["linkedSections"] = json!([{ "parent": 2 }]);
+        gql_response["classes"]["nodes"][0]["capacity"] = json!(40);
+        gql_response["classes"]["nodes"][0]["enrollment"] = json!(42);
+        gql_response["classes"]["nodes"][0]["waitlist"] = json!(3);
+        gql_response["classes"]["nodes"][1]["capacity"] = json!(20);
+        gql_response["classes"]["nodes"][1]["enrollment"] = json!(12);
+        let ctx = CourseListContext::from_value(&gql_response).unwrap();
+
+        let seats: Vec<(u64, Option<u64>, Option<u64>)> = ctx.meetings_from_lectures(&[1]).unwrap().iter()
+            .map(|meeting| (meeting.section_id, meeting.open_seats, meeting.waitlist))
+            .collect();
+        assert_eq!(seats, vec![(1, Some(0), Some(3)), (2, Some(8), None)]);
+    }
+
+    #[test]
+    fn unknown_meeting_type_is_an_error() {
+        let mut gql_response = lecture_with_lab(json!("1400"));
+        gql_response["classes"]["nodes"][0]["meetings"][0]["meetingType"] = json!(9);
+        let ctx = CourseListContext::from_value(&gql_response).unwrap();
+
+        assert_eq!(ctx.meetings_from_lectures(&[1]).unwrap_err(), SolveError::UnknownMeetingType { id: 9 });
+    }
     
     #[test]
     fn parse_test() {
         let res = fs::read("data/mess.json");
         let v: Value = serde_json::from_str(std::str::from_utf8(&res.unwrap()).unwrap()).unwrap();
         let want = vec![2023337427, 2023337795,  2023336415, 2023337412];
-        let course_ctx = CourseListContext::new(&v).unwrap();
+        let course_ctx = CourseListContext::from_value(&v).unwrap();
         let meetings = course_ctx.meetings_from_lectures(&want);
         meetings.iter().for_each(|v| { println!("{:?}", v) });
         // println!("{:?}", v["data"]["classes"]["nodes"].as_array().unwrap().len())
     }
+
+    #[test]
+    fn weekday_bits_match_fixture() {
+        // finals carry their date as well as inSession bits, the date says which bit is which day.
+        // only ones naming a single day, a weekly pattern copied onto an exam says nothing
+        let res = fs::read("data/mess.json");
+        let v: Value = serde_json::from_str(std::str::from_utf8(&res.unwrap()).unwrap()).unwrap();
+        let course_ctx = CourseListContext::from_value(&v).unwrap();
+
+        let mut n_checked = 0;
+        for section in course_ctx.id_to_course.values() {
+            for meeting in &section.meetings {
+                if course_ctx.meeting_type_from_id(meeting.meeting_type) != Ok(MeetingType::Exam) || meeting.in_session.count_ones() != 1 { continue; }
+                let Some(date) = meeting.begin_date.as_deref().and_then(Date::parse) else { continue };
+
+                assert_eq!(Weekday::from_bits(meeting.in_session), vec![date.weekday()], "{} on {}", section.course_number, date);
+                n_checked += 1;
+            }
+        }
+        assert!(n_checked > 0);
+    }
+
+    #[test]
+    fn exams_go_by_their_date() {
+        let v = json!({
+            "meetingTypes": [{ "id": 1, "name": "Lecture" }, { "id": 4, "name": "Exam" }],
+            "classes": { "nodes": [
+                { "id": 1, "courseNumber": "CSE-150-01", "linkedSections": [], "meetings": [
+                    { "inSession": 20, "meetingType": 1, "beginTime": "1030", "endTime": "1145" },
+                    { "inSession": 0, "meetingType": 4, "beginTime": "0800", "endTime": "1100",
+                        "beginDate": "2024-12-10", "endDate": "2024-12-10" }
+                ] },
+                { "id": 2, "courseNumber": "ANTH-1-01", "linkedSections": [], "meetings": [
+                    { "inSession": 2, "meetingType": 4, "beginTime": "1500", "endTime": "1800",
+                        "beginDate": "2024-12-12", "endDate": "2024-12-12" }
+                ] }
+            ] }
+        });
+        let course_ctx = CourseListContext::from_value(&v).unwrap();
+        let meetings = course_ctx.meetings_from_lectures(&[1, 2]).unwrap();
+        let exam_of = |name: &str| -> Vec<(Weekday, u64)> {
+            meetings.iter()
+                .filter(|m| m.section_name == name && m.is_exam())
+                .map(|m| (m.weekday(), m.u_start % (24 * 3600)))
+                .collect()
+        };
+
+        // without inSession bits or against them, a tuesday and a thursday
+        assert_eq!(exam_of("CSE-150-01"), vec![(Weekday::Tuesday, 8 * 3600)]);
+        assert_eq!(exam_of("ANTH-1-01"), vec![(Weekday::Thursday, 15 * 3600)]);
+    }
 }
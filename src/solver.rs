@@ -1,9 +1,18 @@
-use std::collections::HashMap;
+use std::borrow::Borrow;
+use std::cmp::{Ordering, Reverse};
+use std::fmt;
+use std::collections::BinaryHeap;
+use std::sync::Arc;
+use std::sync::atomic::{self, AtomicBool, AtomicU64};
 use log::warn;
+use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::wasm_bindgen;
 
-use crate::parse::{CourseListContext, SectionMeeting, MeetingType};
-use crate::utils::SolveError;
+use crate::calendar::Weekday;
+use crate::constraint::Constraint;
+use crate::parse::{CourseListContext, CourseRequest, SectionMeeting, MeetingType, WantedCourse};
+use crate::utils::{now_ms, BitSet, SolveError};
+use crate::walking::WalkingTimes;
 
 #[wasm_bindgen]
 extern {
@@ -14,193 +23,2081 @@ extern {
 
 #[derive(Debug)]
 pub struct CoursePreferences {
-    // 0, 1, 2, 3 are lecture ids for any 4 classes
-    lecture_ids: Vec<u64>, 
+    // courses[i] holds the lecture sections that can satisfy the i-th wanted course
+    courses: Vec<Vec<LectureOption>>,
+    // optional[i] is whether courses[i] can be left out
+    optional: Vec<bool>,
+    // the course code or lecture section each was asked for as, for explanations
+    names: Vec<String>,
+
+    // how many courses a schedule has, required ones included
+    min_courses: usize,
+    max_courses: usize,
+
+    // total units a schedule may carry
+    min_units: f64,
+    max_units: f64,
+    // units_left[i] is the most units courses i.. can add
+    units_left: Vec<f64>,
 
     // indexed from 0 to whatever as 'internalized' idx for faster lookup and bitmasking
     sections: Vec<SectionMeeting>,
 
-    // grouping by discussion and lab sections sections_idx indexed by lecture_ids' idx
-    lab_sections: Vec<Vec<usize>>,
-    lecture_sections: Vec<Vec<usize>>,
-    discussion_sections: Vec<Vec<usize>>,
-    sentinel_idx: usize,
+    // conflicts[i] holds every section that overlaps section i
+    conflicts: Vec<BitSet>,
+
+    // sections ruled out by the hard constraints
+    excluded: BitSet,
+
+    // tight[i] holds the sections too close to i to walk between, empty without a walking table
+    tight: Vec<BitSet>,
+    // whether tight transitions are conflicts rather than a penalty
+    reject_tight: bool
+}
+
+// one lecture section of a course along with the labs and discussions linked to it
+#[derive(Debug)]
+struct LectureOption {
+    lecture_id: u64,
+    // from the lecture's class node, sections without units count as 0
+    units: f64,
+
+    // lecture meetings and exams, all of them get added or none
+    required: Vec<usize>,
+
+    // one group gets picked from each, a group is every meeting of one section.
+    // courses without labs or discussions get a single empty group
+    labs: Vec<Vec<usize>>,
+    discussions: Vec<Vec<usize>>,
+
+    // pairings[i] lists the discussions allowed with labs[i] by linkedSections
+    pairings: Vec<Vec<usize>>,
+}
+
+// soft preferences, all times are seconds after midnight and penalties are per hour
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SchedulePreferences {
+    pub earliest_start: Option<u64>,
+    pub latest_end: Option<u64>,
+    pub days_off: Vec<Weekday>,
+    pub lunch_breaks: Vec<LunchBreak>,
+    // sections taught by these earn instructor_weight each
+    pub preferred_instructors: Vec<String>,
+    // walking_weight is per pair of meetings too close to walk between, see with_walking_times
+
+    pub early_weight: f64,
+    pub late_weight: f64,
+    pub day_off_weight: f64,
+    pub gap_weight: f64,
+    pub lunch_weight: f64,
+    pub instructor_weight: f64,
+    pub walking_weight: f64,
+}
+
+// wants `duration` free seconds somewhere between `start` and `end` on every day with classes
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LunchBreak {
+    pub start: u64,
+    pub end: u64,
+    pub duration: u64,
+}
+
+impl Default for SchedulePreferences {
+    fn default() -> Self {
+        Self {
+            earliest_start: None,
+            latest_end: None,
+            days_off: Vec::new(),
+            lunch_breaks: Vec::new(),
+            preferred_instructors: Vec::new(),
+
+            early_weight: 1.0,
+            late_weight: 1.0,
+            day_off_weight: 4.0,
+            gap_weight: 0.5,
+            lunch_weight: 2.0,
+            instructor_weight: 2.0,
+            walking_weight: 2.0,
+        }
+    }
+}
+
+// one solution, sections sorted by start
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Schedule {
+    pub sections: Vec<SectionMeeting>,
+    // the lecture section taken for each course, in the order they were asked for. online
+    // sections have no meetings so this is the only place they show up
+    #[serde(default)]
+    pub lectures: Vec<u64>,
+    pub units: f64,
+    // higher is better, 0.0 means no preference was violated or met
+    pub score: f64,
+}
+
+// when to stop searching early, anything left out doesn't limit it
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SolveOptions {
+    // schedules found, not kept. solve_top_k_with still ranks only these
+    pub max_solutions: Option<usize>,
+    // partial schedules looked at
+    pub max_nodes: Option<u64>,
+    // milliseconds after the search starts
+    pub deadline: Option<f64>,
+    #[serde(skip)]
+    pub cancel: Option<CancelFlag>,
+}
+
+impl SolveOptions {
+    // what's left for another search once nodes and elapsed milliseconds of this were used up
+    pub fn remaining(&self, nodes: u64, elapsed: f64) -> SolveOptions {
+        SolveOptions {
+            max_solutions: self.max_solutions,
+            max_nodes: self.max_nodes.map(|max| max.saturating_sub(nodes)),
+            deadline: self.deadline.map(|ms| ms - elapsed),
+            cancel: self.cancel.clone(),
+        }
+    }
+}
+
+// set from anywhere holding a clone to stop a search the next time it checks. wasm runs the
+// search on the js thread, so there it only works between batches of a stream
+#[wasm_bindgen]
+#[derive(Debug, Clone, Default)]
+pub struct CancelFlag {
+    cancelled: Arc<AtomicBool>,
+}
+
+#[wasm_bindgen]
+impl CancelFlag {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, atomic::Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(atomic::Ordering::Relaxed)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SearchStatus {
+    // every schedule was considered
+    Exhaustive,
+    // stopped early, there may be more or better schedules
+    SolutionLimit,
+    NodeLimit,
+    Deadline,
+    Cancelled,
+}
+
+impl SearchStatus {
+    pub fn is_exhaustive(self) -> bool {
+        self == Self::Exhaustive
+    }
+}
+
+impl fmt::Display for SearchStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Exhaustive => write!(f, "searched everything"),
+            Self::SolutionLimit => write!(f, "stopped at the solution limit"),
+            Self::NodeLimit => write!(f, "stopped at the node limit"),
+            Self::Deadline => write!(f, "ran out of time"),
+            Self::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SolveResult {
+    // best first
+    pub schedules: Vec<Schedule>,
+    pub status: SearchStatus,
+    // partial schedules looked at, and how many of them were cut off
+    pub nodes: u64,
+    pub pruned: u64,
+}
+
+// how far along a search is, for progress bars
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Progress {
+    pub nodes: u64,
+    // schedules found so far, including ones solve_top_k didn't keep
+    pub found: usize,
+    // a guess at the share of the search still to go, from 1.0 down to 0.0 once it's done.
+    // it assumes every way of taking a course leads to the same amount of work
+    pub remaining: f64,
+}
+
+// nodes between progress reports
+const PROGRESS_EVERY: u64 = 1 << 14;
+
+impl Schedule {
+    // what tells two schedules apart, sorted and each id once
+    pub fn section_ids(&self) -> Vec<u64> {
+        let mut section_ids: Vec<u64> = self.sections.iter().map(|section| section.section_id)
+            .chain(self.lectures.iter().copied())
+            .collect();
+        section_ids.sort();
+        section_ids.dedup();
+        section_ids
+    }
+}
+
+// what explain_infeasible found
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum Infeasible {
+    // the constraints rule out every way of taking this course, even on its own
+    NoValidSection { course: String },
+    // these courses can't all be taken together, and every one of them is part of the problem
+    Clash { courses: Vec<String>, overlaps: Vec<Overlap> },
+    // the required courses fit together, just not within the course count or unit range
+    OutsideLimits,
+}
+
+// one meeting and the sections of the other courses it runs into
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Overlap {
+    pub section: String,
+    pub with: Vec<String>,
+}
 
-    exam_sections: Vec<Option<usize>>
+impl fmt::Display for Infeasible {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NoValidSection { course } => write!(f, "no section of {} fits the constraints", course),
+            Self::Clash { courses, overlaps } => {
+                write!(f, "{} can't be taken together", courses.join(", "))?;
+                for overlap in overlaps {
+                    write!(f, "\n  {} overlaps {}", overlap.section, overlap.with.join(", "))?;
+                }
+                Ok(())
+            }
+            Self::OutsideLimits => write!(f, "the required courses fit together, but not within the course or unit limits"),
+        }
+    }
 }
 
 pub struct BTSolver {
     prefs: CoursePreferences,
+    schedule_prefs: SchedulePreferences,
+
+    // sections taught by a preferred instructor
+    preferred: BitSet,
+    // bonus_left[i] is the most instructor bonus courses i.. can add
+    bonus_left: Vec<f64>,
 }
 
 impl CoursePreferences {
-    pub fn new(lecture_ids: Vec<u64>, course_ctx: CourseListContext) -> Result<Self, SolveError> {
-        let mut lab_sections = vec![Vec::new(); lecture_ids.len()];
-        let mut discussion_sections = vec![Vec::new(); lecture_ids.len()];
-        let mut lecture_sections = vec![Vec::new(); lecture_ids.len()];
-        let mut exam_sections = vec![None; lecture_ids.len()];
-
-        let mut lecture_id_to_idx: HashMap<u64, usize> = HashMap::new();
-        lecture_ids.iter().enumerate().for_each(|(i, k)| {
-            lecture_id_to_idx.insert(*k, i);
-        });
+    // each wanted course is a lecture id or a course code like CSE-150, in which case
+    // every lecture section of it is tried. optional courses are only taken if they fit
+    pub fn new<R: Into<WantedCourse>>(want: Vec<R>, course_ctx: impl Borrow<CourseListContext>) -> Result<Self, SolveError> {
+        let course_ctx = course_ctx.borrow();
+        // (course idx, option idx, meeting) so the meetings can be sorted before getting their idx
+        let mut tagged = Vec::new();
+        let mut courses = Vec::new();
+        let mut optional = Vec::new();
+        let mut names = Vec::new();
 
-        let mut sections = course_ctx.meetings_from_lectures(&lecture_ids)?;
-        sections.sort_by_key(|section| section.u_start);
+        // requests sharing a lecture section, like CSE-150, cse-150 and the id of one of its
+        // lectures, are one course. otherwise it could be taken twice and show up as two schedules.
+        // each group is (lecture ids, optional, name) per request, in the order they were asked for
+        let mut groups: Vec<Vec<(Vec<u64>, bool, String)>> = Vec::new();
+        for wanted in want {
+            let wanted = wanted.into();
+            let lecture_ids = course_ctx.lecture_options(&wanted.course)?;
+            let name = match &wanted.course {
+                CourseRequest::Course(code) => code.clone(),
+                CourseRequest::Lecture(id) => course_ctx.id_to_course.get(id)
+                    .map_or_else(|| id.to_string(), |lecture| lecture.course_number.clone()),
+            };
 
-        sections.iter().enumerate().for_each(|(idx, section)| {
-            let lecture_idx = lecture_id_to_idx[&section.lecture_id];
+            // it can join groups that didn't share anything before, they go where the first one was
+            let shares = |group: &Vec<(Vec<u64>, bool, String)>| group.iter().any(|(ids, _, _)| ids.iter().any(|id| lecture_ids.contains(id)));
+            let touching: Vec<usize> = (0..groups.len()).filter(|&idx| shares(&groups[idx])).collect();
+            let request = (lecture_ids, wanted.optional, name);
+            match touching.split_first() {
+                None => groups.push(vec![request]),
+                Some((&first, rest)) => {
+                    for &idx in rest.iter().rev() {
+                        let group = groups.remove(idx);
+                        groups[first].extend(group);
+                    }
+                    groups[first].push(request);
+                }
+            }
+        }
 
-            match section.meeting_type {
-                MeetingType::Lecture => lecture_sections[lecture_idx].push(idx),
-                MeetingType::Discussion => discussion_sections[lecture_idx].push(idx),
-                MeetingType::Lab => lab_sections[lecture_idx].push(idx),
-                MeetingType::Exam => exam_sections[lecture_idx] = Some(idx),
-                MeetingType::Other => warn!("building course preferences index OTHER: {:?}", section),
-            };
-        });
+        for (course_idx, group) in groups.into_iter().enumerate() {
+            let (lecture_ids, is_optional) = merge_requests(&group)?;
+            optional.push(is_optional);
+            names.push(group[0].2.clone());
 
-        let sentinel_idx = sections.len();
-        // this is for courses without lab or discussion or exam. just give a sentinel idx past sections' size 
-        for lecture_idx in 0..lecture_ids.len() {
-            if lab_sections[lecture_idx].len() == 0 {
-                lab_sections[lecture_idx].push(sentinel_idx);
+            let mut options = Vec::new();
+            for (option_idx, lecture_id) in lecture_ids.into_iter().enumerate() {
+                let meetings = course_ctx.meetings_from_lectures(&[lecture_id])?;
+                tagged.extend(meetings.into_iter().map(|meeting| (course_idx, option_idx, meeting)));
+                let units = course_ctx.id_to_course.get(&lecture_id).and_then(|lecture| lecture.units).unwrap_or(0.0);
+                options.push(LectureOption {
+                    lecture_id, units, required: Vec::new(), labs: Vec::new(), discussions: Vec::new(), pairings: Vec::new()
+                });
             }
-    
-            if discussion_sections[lecture_idx].len() == 0 {
-                discussion_sections[lecture_idx].push(sentinel_idx);
+            courses.push(options);
+        }
+
+        tagged.sort_by_key(|(_, _, section)| section.u_start);
+
+        let mut sections: Vec<SectionMeeting> = Vec::with_capacity(tagged.len());
+        for (idx, (course_idx, option_idx, section)) in tagged.into_iter().enumerate() {
+            let option = &mut courses[course_idx][option_idx];
+
+            let groups = match section.meeting_type {
+                MeetingType::Lecture | MeetingType::Exam => { option.required.push(idx); None },
+                MeetingType::Lab => Some(&mut option.labs),
+                MeetingType::Discussion => Some(&mut option.discussions),
+                MeetingType::Other => { warn!("building course preferences index OTHER: {:?}", section); None },
+            };
+
+            if let Some(groups) = groups {
+                match groups.iter_mut().find(|group| sections[group[0]].section_id == section.section_id) {
+                    Some(group) => group.push(idx),
+                    None => groups.push(vec![idx]),
+                }
             }
+            sections.push(section);
         }
 
+        for option in courses.iter_mut().flatten() {
+            let group_ids = |groups: &[Vec<usize>]| -> Vec<u64> {
+                groups.iter().map(|group| sections[group[0]].section_id).collect()
+            };
+            let lab_ids = group_ids(&option.labs);
+            let discussion_ids = group_ids(&option.discussions);
+
+            // this is for courses without lab or discussion, there is just nothing to pick
+            if option.labs.is_empty() { option.labs.push(Vec::new()); }
+            if option.discussions.is_empty() { option.discussions.push(Vec::new()); }
+
+            option.pairings = option.labs.iter().enumerate().map(|(lab_idx, _)| {
+                (0..option.discussions.len()).filter(|&discussion_idx| {
+                    match (lab_ids.get(lab_idx), discussion_ids.get(discussion_idx)) {
+                        (Some(&lab_id), Some(&discussion_id)) =>
+                            course_ctx.may_pair(lab_id, discussion_id, &lab_ids, &discussion_ids),
+                        _ => true,
+                    }
+                }).collect()
+            }).collect();
+        }
+
+        let mut units_left = vec![0.0; courses.len() + 1];
+        for (course_idx, options) in courses.iter().enumerate().rev() {
+            let most = options.iter().map(|option| option.units).fold(0.0, f64::max);
+            units_left[course_idx] = units_left[course_idx + 1] + most;
+        }
+
+        let conflicts = conflict_rows(&sections);
+        let excluded = BitSet::new(sections.len());
+
         Ok(Self {
-            lecture_ids,
+            courses,
+            optional,
+            names,
+
+            min_courses: 0,
+            max_courses: usize::MAX,
+
+            min_units: 0.0,
+            max_units: f64::INFINITY,
+            units_left,
+
             sections,
 
-            lab_sections,
-            lecture_sections,
-            discussion_sections,
-            sentinel_idx,
+            conflicts,
 
-            exam_sections
+            excluded,
+
+            tight: Vec::new(),
+            reject_tight: false
         })
     }
+
+    // e.g. two required courses plus any two of four optional ones is min = max = 4
+    pub fn with_course_count(mut self, min_courses: Option<usize>, max_courses: Option<usize>) -> Self {
+        self.min_courses = min_courses.unwrap_or(0);
+        self.max_courses = max_courses.unwrap_or(usize::MAX);
+        self
+    }
+
+    // full-time minimum and overload maximum, in the catalog's units
+    pub fn with_unit_range(mut self, min_units: Option<f64>, max_units: Option<f64>) -> Self {
+        self.min_units = min_units.unwrap_or(0.0);
+        self.max_units = max_units.unwrap_or(f64::INFINITY);
+        self
+    }
+
+    // strict makes meetings too close to walk between a conflict instead of a penalty
+    pub fn with_walking_times(mut self, walking_times: &WalkingTimes, strict: bool) -> Self {
+        self.tight = tight_rows(&self.sections, walking_times);
+        self.reject_tight = strict;
+        self
+    }
+
+    pub fn with_constraints(mut self, constraints: &[Constraint]) -> Self {
+        self.excluded = BitSet::new(self.sections.len());
+        self.sections.iter().enumerate()
+            .filter(|(_, section)| constraints.iter().any(|constraint| constraint.excludes(section)))
+            .for_each(|(idx, _)| self.excluded.insert(idx));
+
+        self
+    }
+
+    // whether section idx is allowed and can be added without overlapping anything in schedule_mask
+    fn fits(&self, idx: usize, schedule_mask: &BitSet) -> bool {
+        let too_far = self.reject_tight && self.tight.get(idx).is_some_and(|tight| tight.intersects(schedule_mask));
+        !self.excluded.contains(idx) && !self.conflicts[idx].intersects(schedule_mask) && !too_far
+    }
+}
+
+// requests for one course, as (lecture ids, optional, name). it's required if any of them is,
+// then every required one has to be satisfied by the lecture taken. all optional and any will do
+fn merge_requests(group: &[(Vec<u64>, bool, String)]) -> Result<(Vec<u64>, bool), SolveError> {
+    let required: Vec<&(Vec<u64>, bool, String)> = group.iter().filter(|(_, optional, _)| !optional).collect();
+
+    let mut lecture_ids: Vec<u64> = match required.as_slice() {
+        [] => group.iter().flat_map(|(ids, _, _)| ids.iter().copied()).collect(),
+        [(ids, _, _), ..] => ids.iter().copied()
+            .filter(|id| required.iter().all(|(other_ids, _, _)| other_ids.contains(id)))
+            .collect(),
+    };
+    lecture_ids.sort();
+    lecture_ids.dedup();
+
+    if lecture_ids.is_empty() {
+        let names: Vec<&str> = required.iter().map(|(_, _, name)| name.as_str()).collect();
+        return Err(SolveError::InvalidPreferences {
+            reason: format!("{} are the same course but no lecture section satisfies all of them", names.join(", ")),
+        });
+    }
+    Ok((lecture_ids, required.is_empty()))
 }
 
-fn mask_values(indices: &Vec<usize>, schedule_mask: &mut Vec<bool>, val: bool) {
-    indices.iter().for_each(|&idx| {
-        schedule_mask[idx] = val;
-    });
+// sections must be sorted by start, so only the ones starting before i ends need checking
+fn conflict_rows(sections: &[SectionMeeting]) -> Vec<BitSet> {
+    let mut conflicts = vec![BitSet::new(sections.len()); sections.len()];
+
+    for (i, section) in sections.iter().enumerate() {
+        for (j, other) in sections.iter().enumerate().skip(i + 1) {
+            if other.u_start > section.u_end { break; }
+            if !section.conflicts_with(other) { continue; }
+
+            conflicts[i].insert(j);
+            conflicts[j].insert(i);
+        }
+    }
+
+    conflicts
 }
 
+// weekly meetings that don't overlap but leave less time between them than the walk takes.
+// sections must be sorted by start like in conflict_rows
+fn tight_rows(sections: &[SectionMeeting], walking_times: &WalkingTimes) -> Vec<BitSet> {
+    let mut tight = vec![BitSet::new(sections.len()); sections.len()];
+    let longest = walking_times.longest();
+
+    for (i, section) in sections.iter().enumerate() {
+        let Some(building) = section.building.as_deref() else { continue };
+        if section.is_exam() { continue; }
+
+        for (j, other) in sections.iter().enumerate().skip(i + 1) {
+            // the table can hold anything, u64::MAX means never
+            if other.u_start > section.u_end.saturating_add(longest) { break; }
+            // overlapping is already a conflict
+            if other.is_exam() || other.u_start <= section.u_end { continue; }
+            let Some(other_building) = other.building.as_deref() else { continue };
+
+            let gap = other.u_start - section.u_end;
+            if walking_times.between(building, other_building).is_some_and(|walk| gap < walk) {
+                tight[i].insert(j);
+                tight[j].insert(i);
+            }
+        }
+    }
+
+    tight
+}
+
+const DAY: u64 = 24 * 3600;
+const HOUR: f64 = 3600.0;
+
 impl BTSolver {
     pub fn new(prefs: CoursePreferences) -> Self {
+        let n_sections = prefs.sections.len();
         BTSolver {
-            prefs
+            prefs,
+            schedule_prefs: SchedulePreferences::default(),
+            preferred: BitSet::new(n_sections),
+            bonus_left: Vec::new(),
+        }.with_preferences(SchedulePreferences::default())
+    }
+
+    pub fn with_preferences(mut self, schedule_prefs: SchedulePreferences) -> Self {
+        self.preferred = BitSet::new(self.prefs.sections.len());
+        self.prefs.sections.iter().enumerate()
+            .filter(|(_, section)| section.taught_by_any(&schedule_prefs.preferred_instructors))
+            .for_each(|(idx, _)| self.preferred.insert(idx));
+
+        // the best pick of each course, taking the lab and discussion bonus separately is
+        // an overestimate when they aren't paired but that is fine for a bound
+        let group_bonus = |groups: &[Vec<usize>]| -> f64 {
+            let any_preferred = groups.iter().any(|group| group.iter().any(|&idx| self.preferred.contains(idx)));
+            if any_preferred { schedule_prefs.instructor_weight } else { 0.0 }
+        };
+        self.bonus_left = vec![0.0; self.prefs.courses.len() + 1];
+        for (course_idx, options) in self.prefs.courses.iter().enumerate().rev() {
+            let most = options.iter()
+                .map(|option| group_bonus(std::slice::from_ref(&option.required)) + group_bonus(&option.labs) + group_bonus(&option.discussions))
+                .fold(0.0, f64::max);
+            self.bonus_left[course_idx] = self.bonus_left[course_idx + 1] + most;
         }
+
+        self.schedule_prefs = schedule_prefs;
+        self
+    }
+
+    // best scoring schedules first, ties keep search order
+    pub fn solve(&self) -> Vec<Schedule> {
+        self.solve_with(&SolveOptions::default()).schedules
+    }
+
+    // same as solve but giving up once options runs out, with whatever was found by then.
+    // with the parallel feature a big max_nodes still splits the search, so like a deadline
+    // what's found before it runs out can change from run to run. max_solutions keeps it on
+    // one thread and always gives the first ones in search order
+    pub fn solve_with(&self, options: &SolveOptions) -> SolveResult {
+        self.run(options, None)
     }
 
-    pub fn solve(&self) -> Vec<Vec<SectionMeeting>> {
-        let mut schedule_mask = vec![false; self.prefs.sections.len()];
-        let mut solutions = Vec::new();
+    // solve_with that calls on_progress every so often and once more at the end. returning
+    // false from it stops the search as if it was cancelled. always runs on one thread
+    pub fn solve_with_progress(&self, options: &SolveOptions, mut on_progress: impl FnMut(&Progress) -> bool) -> SolveResult {
+        let mut search = self.search(options);
+        let res = self.run_search(&mut search, None, &mut on_progress);
+        on_progress(&search.progress(self));
+        res
+    }
 
-        self.search(&mut solutions, 0, 0, &mut schedule_mask);
-        
-        solutions
+    // same order as solve but only the best k, skipping subtrees that can't beat the k-th best
+    pub fn solve_top_k(&self, k: usize) -> Vec<Schedule> {
+        self.solve_top_k_with(k, &SolveOptions::default()).schedules
     }
 
-    fn search(&self, solutions: &mut Vec<Vec<SectionMeeting>>, n_added: usize, n_considered: usize, schedule_mask: &mut Vec<bool>) -> usize {
-        if n_added == self.prefs.lecture_ids.len() { 
-            let mut valid_sections = Vec::new();
-            schedule_mask.iter().enumerate().for_each(|(idx, &val)| {
-                if val {
-                    valid_sections.push(self.prefs.sections[idx].clone());
+    pub fn solve_top_k_with(&self, k: usize, options: &SolveOptions) -> SolveResult {
+        if k == 0 {
+            return SolveResult { schedules: Vec::new(), status: SearchStatus::Exhaustive, nodes: 0, pruned: 0 };
+        }
+        self.run(options, Some(k))
+    }
+
+    // with the parallel feature, a small node budget keeps it on one thread since splitting the
+    // search up could use all of it. so does a solution budget, which pieces finish first
+    // would decide which schedules are kept
+    fn run(&self, options: &SolveOptions, k: Option<usize>) -> SolveResult {
+        let mut search = self.search(options);
+
+        #[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+        if self.prefs.courses.len() > 1 && options.max_solutions.is_none() && options.max_nodes.is_none_or(|max| max >= 1 << 16) {
+            return self.run_split(search, k);
+        }
+
+        self.run_search(&mut search, k, &mut keep_going)
+    }
+
+    // everything search finds, or the best k of it, best first
+    fn run_search(&self, search: &mut Search, k: Option<usize>, report: &mut dyn FnMut(&Progress) -> bool) -> SolveResult {
+        let schedules = match k {
+            None => {
+                let mut solutions = Vec::new();
+                while let Some(schedule) = search.next(self, report) {
+                    solutions.push(schedule);
+                }
+                solutions.sort_by(|a, b| b.score.total_cmp(&a.score));
+                solutions
+            }
+            Some(k) => {
+                let mut top_k = TopK { k, n_seen: 0, heap: BinaryHeap::with_capacity(k + 1) };
+                while let Some(schedule) = search.next(self, report) {
+                    top_k.add(schedule);
+                    search.threshold = top_k.threshold();
                 }
-            });
+                top_k.heap.into_sorted_vec().into_iter()
+                    .map(|Reverse(ranked)| ranked.schedule)
+                    .collect()
+            }
+        };
+
+        search.result(schedules)
+    }
+
+    // each way of taking the first few courses searched on its own, then put back together in the
+    // order one thread would have found them in. the sorts are stable so ties come out the same.
+    // a node budget is shared between the pieces, so where it stops depends on timing
+    #[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+    fn run_split(&self, search: Search, k: Option<usize>) -> SolveResult {
+        use rayon::prelude::*;
+
+        // enough pieces that one slow one doesn't leave the other threads waiting
+        let target = 4 * rayon::current_num_threads();
+        let (mut nodes, mut pruned) = (0, 0);
+        let mut branches = vec![search];
+        while branches.len() < target && branches.iter().any(Search::can_split) {
+            let mut next = Vec::with_capacity(branches.len());
+            for branch in branches {
+                if !branch.can_split() {
+                    next.push(branch);
+                    continue;
+                }
+                let (mut parent, children) = branch.split(self);
+                parent.flush();
+                (nodes, pruned) = (nodes + parent.nodes, pruned + parent.pruned);
+                next.extend(children);
+            }
+            branches = next;
+        }
+
+        let results: Vec<SolveResult> = branches.into_par_iter()
+            .map(|mut branch| {
+                let res = self.run_search(&mut branch, k, &mut keep_going);
+                branch.flush();
+                res
+            })
+            .collect();
+
+        let mut merged = SolveResult { schedules: Vec::new(), status: SearchStatus::Exhaustive, nodes, pruned };
+        for res in results {
+            merged.schedules.extend(res.schedules);
+            merged.nodes += res.nodes;
+            merged.pruned += res.pruned;
+            if merged.status.is_exhaustive() { merged.status = res.status; }
+        }
+
+        merged.schedules.sort_by(|a, b| b.score.total_cmp(&a.score));
+        if let Some(k) = k { merged.schedules.truncate(k); }
+        merged
+    }
+
+    fn search(&self, options: &SolveOptions) -> Search {
+        Search::new(self, (0..self.prefs.courses.len()).collect(), true).with_budget(options)
+    }
+
+    // schedules one at a time in search order, not sorted by score
+    pub fn iter(&self) -> Schedules<&BTSolver> {
+        self.iter_with(&SolveOptions::default())
+    }
+
+    pub fn iter_with(&self, options: &SolveOptions) -> Schedules<&BTSolver> {
+        Schedules { search: self.search(options), solver: self }
+    }
+
+    // same as iter_with but owning the solver, for handing to js
+    pub fn into_schedules(self, options: &SolveOptions) -> Schedules<BTSolver> {
+        Schedules { search: self.search(options), solver: self }
+    }
+
+    // adds all of indices or none of them if any would overlap
+    fn take(&self, indices: &[usize], schedule_mask: &mut BitSet) -> bool {
+        for (n_taken, &idx) in indices.iter().enumerate() {
+            if !self.prefs.fits(idx, schedule_mask) {
+                indices[..n_taken].iter().for_each(|&idx| schedule_mask.remove(idx));
+                return false;
+            }
+            schedule_mask.insert(idx);
+        }
+
+        true
+    }
+
+    // why solve comes back empty, None when it doesn't. clashes are narrowed down to a
+    // smallest set of required courses that can't be taken together
+    pub fn explain_infeasible(&self) -> Option<Infeasible> {
+        if self.iter().next().is_some() { return None; }
+        self.explain_infeasible_with(&SolveOptions::default())
+    }
+
+    // explain_infeasible for when a search already came back empty, so it doesn't go again.
+    // the searches it takes share options' nodes and deadline, None if they run out first
+    pub fn explain_infeasible_with(&self, options: &SolveOptions) -> Option<Infeasible> {
+        let started = now_ms();
+        let mut nodes = 0;
+        // whether there's any way to take all of courses together, ignoring course and unit limits
+        let mut can_take = |courses: &[usize]| -> Option<bool> {
+            let budget = SolveOptions { max_solutions: None, ..options.remaining(nodes, now_ms() - started) };
+            let mut search = Search::new(self, courses.to_vec(), false).with_budget(&budget);
+            let found = search.next(self, &mut keep_going).is_some();
+            nodes += search.nodes;
+            match search.stopped {
+                Some(_) => None,
+                None => Some(found),
+            }
+        };
+
+        let mut core: Vec<usize> = (0..self.prefs.courses.len()).filter(|&idx| !self.prefs.optional[idx]).collect();
+        if can_take(&core)? { return Some(Infeasible::OutsideLimits); }
+
+        // drop every course the clash still happens without
+        let mut i = 0;
+        while i < core.len() {
+            let mut without = core.clone();
+            without.remove(i);
+
+            match can_take(&without)? {
+                true => i += 1,
+                false => core = without,
+            }
+        }
+
+        let names = core.iter().map(|&course_idx| self.prefs.names[course_idx].clone()).collect();
+        match core.as_slice() {
+            [course_idx] => Some(Infeasible::NoValidSection { course: self.prefs.names[*course_idx].clone() }),
+            _ => Some(Infeasible::Clash { courses: names, overlaps: self.overlaps(&core) }),
+        }
+    }
+
+    // meetings of one course that clash with sections of a later one, using the same
+    // conflict rows the search checks
+    fn overlaps(&self, courses: &[usize]) -> Vec<Overlap> {
+        let prefs = &self.prefs;
+        let indices_of = |course_idx: usize| -> Vec<usize> {
+            let mut indices: Vec<usize> = prefs.courses[course_idx].iter()
+                .flat_map(|option| option.required.iter().chain(option.labs.iter().flatten()).chain(option.discussions.iter().flatten()))
+                .copied()
+                .filter(|&idx| !prefs.excluded.contains(idx))
+                .collect();
+            indices.sort();
+            indices.dedup();
+            indices
+        };
+
+        let mut overlaps: Vec<Overlap> = Vec::new();
+        for (i, &course_idx) in courses.iter().enumerate() {
+            let others: Vec<usize> = courses[i + 1..].iter().flat_map(|&other_idx| indices_of(other_idx)).collect();
+
+            for idx in indices_of(course_idx) {
+                let clashes = |other: &usize| prefs.conflicts[idx].contains(*other)
+                    || (prefs.reject_tight && prefs.tight.get(idx).is_some_and(|tight| tight.contains(*other)));
+
+                let section = prefs.sections[idx].describe();
+                for other in others.iter().filter(|other| clashes(other)) {
+                    let other_name = &prefs.sections[*other].section_name;
+                    let overlap = match overlaps.iter_mut().find(|overlap| overlap.section == section) {
+                        Some(overlap) => overlap,
+                        None => {
+                            overlaps.push(Overlap { section: section.clone(), with: Vec::new() });
+                            overlaps.last_mut().unwrap()
+                        }
+                    };
+                    if !overlap.with.contains(other_name) { overlap.with.push(other_name.clone()); }
+                }
+            }
+        }
+
+        overlaps
+    }
+
+    fn schedule_of(&self, schedule_mask: &BitSet, lectures: Vec<u64>, units: f64) -> Schedule {
+        Schedule {
+            sections: schedule_mask.iter().map(|idx| self.prefs.sections[idx].clone()).collect(),
+            lectures,
+            units,
+            score: self.score(schedule_mask),
+        }
+    }
+
+    // higher is better, 0.0 means no preference was violated or met
+    fn score(&self, schedule_mask: &BitSet) -> f64 {
+        self.bonus(schedule_mask) - self.penalty(schedule_mask, true)
+    }
+
+    // best score any schedule containing schedule_mask can reach once courses n_considered..
+    // are decided. every penalty except gaps only grows as sections are added (a new section
+    // can split a gap in two), and the bonus can grow by at most bonus_left
+    fn bound(&self, schedule_mask: &BitSet, n_considered: usize) -> f64 {
+        self.bonus(schedule_mask) + self.bonus_left[n_considered] - self.penalty(schedule_mask, false)
+    }
+
+    // instructor_weight for every section, not meeting, with a preferred instructor
+    fn bonus(&self, schedule_mask: &BitSet) -> f64 {
+        if !self.preferred.intersects(schedule_mask) { return 0.0; }
+
+        let mut section_ids: Vec<u64> = schedule_mask.iter()
+            .filter(|&idx| self.preferred.contains(idx))
+            .map(|idx| self.prefs.sections[idx].section_id)
+            .collect();
+        section_ids.sort();
+        section_ids.dedup();
+
+        self.schedule_prefs.instructor_weight * section_ids.len() as f64
+    }
+
+    fn penalty(&self, schedule_mask: &BitSet, with_gaps: bool) -> f64 {
+        let prefs = &self.schedule_prefs;
+        let mut penalty = 0.0;
+
+        // exams don't happen every week so they don't count against the weekly layout
+        let weekly: Vec<&SectionMeeting> = schedule_mask.iter()
+            .map(|idx| &self.prefs.sections[idx])
+            .filter(|section| !matches!(section.meeting_type, MeetingType::Exam))
+            .collect();
+
+        if !self.prefs.reject_tight && !self.prefs.tight.is_empty() {
+            // every tight pair shows up in both rows
+            let n_tight: usize = schedule_mask.iter().map(|idx| self.prefs.tight[idx].count_common(schedule_mask)).sum();
+            penalty += prefs.walking_weight * (n_tight / 2) as f64;
+        }
+
+        // sections are sorted by start so every day is a contiguous run
+        for day in weekly.chunk_by(|a, b| a.u_start / DAY == b.u_start / DAY) {
+            let day_idx = day[0].u_start / DAY;
+            let day_start = day_idx * DAY;
+
+            if prefs.days_off.contains(&day[0].weekday()) {
+                penalty += prefs.day_off_weight * day.len() as f64;
+            }
+
+            for section in day {
+                if let Some(earliest) = prefs.earliest_start {
+                    let too_early = (day_start + earliest).saturating_sub(section.u_start);
+                    penalty += prefs.early_weight * too_early as f64 / HOUR;
+                }
+                if let Some(latest) = prefs.latest_end {
+                    let too_late = section.u_end.saturating_sub(day_start + latest);
+                    penalty += prefs.late_weight * too_late as f64 / HOUR;
+                }
+            }
+
+            if with_gaps {
+                for pair in day.windows(2) {
+                    let gap = pair[1].u_start.saturating_sub(pair[0].u_end);
+                    penalty += prefs.gap_weight * gap as f64 / HOUR;
+                }
+            }
+
+            for lunch in &prefs.lunch_breaks {
+                if !has_free_time(day, day_start + lunch.start, day_start + lunch.end, lunch.duration) {
+                    penalty += prefs.lunch_weight;
+                }
+            }
+        }
+
+        penalty
+    }
+}
+
+// the search as an explicit stack so it can stop after any schedule and pick up again later
+#[derive(Clone)]
+struct Search {
+    // course idx decided at each depth
+    courses: Vec<usize>,
+    // course count, units and optional courses only count when set
+    limits: bool,
+    // schedules scoring at or below this are not wanted anymore
+    threshold: Option<f64>,
+
+    schedule_mask: BitSet,
+    stack: Vec<Frame>,
+    started: bool,
+    // frames below this belong to whoever split the search off, it's done once it gets back here
+    floor: usize,
+
+    // when to give up, the deadline is on the now_ms clock
+    max_solutions: Option<usize>,
+    max_nodes: Option<u64>,
+    deadline: Option<f64>,
+    cancel: Option<CancelFlag>,
+    // why it gave up, None while it hasn't
+    stopped: Option<SearchStatus>,
+    // nodes used by every piece of a split search between them, only with a node budget
+    spent: Option<Arc<AtomicU64>>,
+    // nodes already added to spent
+    flushed: u64,
+
+    n_found: usize,
+    nodes: u64,
+    pruned: u64,
+    // nodes at the last progress report
+    reported_at: u64,
+}
+
+// one course being decided. a lecture option, then one of its labs, then a discussion paired
+// with that lab go into schedule_mask in turn, so a clashing lecture rules out all its labs at once
+#[derive(Clone, Default)]
+struct Frame {
+    // what is in schedule_mask right now
+    option: Option<usize>,
+    lab: Option<usize>,
+    discussion: Option<usize>,
+
+    // next ones to try
+    next_option: usize,
+    next_lab: usize,
+    next_pairing: usize,
+    skipped: bool,
+
+    // courses and units taken before this one
+    n_taken: usize,
+    units: f64,
+}
+
+impl Frame {
+    // which combination of its course this is on and how many there are, skipping counts as
+    // the one after the last
+    fn position(&self, options: &[LectureOption]) -> (usize, usize) {
+        let n_combinations = |option: &LectureOption| -> usize { option.pairings.iter().map(Vec::len).sum() };
+        let total = options.iter().map(n_combinations).sum();
+
+        let position = match (self.option, self.lab) {
+            _ if self.skipped => total,
+            (Some(option_idx), Some(lab_idx)) => options[..option_idx].iter().map(n_combinations).sum::<usize>()
+                + options[option_idx].pairings[..lab_idx].iter().map(Vec::len).sum::<usize>()
+                + self.next_pairing.saturating_sub(1),
+            _ => 0,
+        };
+        (position, total)
+    }
+}
+
+enum Step {
+    Prune,
+    Complete,
+    Descend,
+}
+
+enum Advance {
+    // a whole lecture + lab + discussion is in schedule_mask, carrying this many units
+    Took(f64),
+    // the course is left out
+    Skipped,
+    Exhausted,
+}
+
+impl Search {
+    fn new(solver: &BTSolver, courses: Vec<usize>, limits: bool) -> Self {
+        Self {
+            courses,
+            limits,
+            threshold: None,
+            schedule_mask: BitSet::new(solver.prefs.sections.len()),
+            stack: Vec::new(),
+            started: false,
+            floor: 0,
+            max_solutions: None,
+            max_nodes: None,
+            deadline: None,
+            cancel: None,
+            stopped: None,
+            spent: None,
+            flushed: 0,
+            n_found: 0,
+            nodes: 0,
+            pruned: 0,
+            reported_at: 0,
+        }
+    }
+
+    fn with_budget(mut self, options: &SolveOptions) -> Self {
+        self.max_solutions = options.max_solutions;
+        self.max_nodes = options.max_nodes;
+        self.deadline = options.deadline.map(|ms| now_ms() + ms);
+        self.cancel = options.cancel.clone();
+        self.spent = options.max_nodes.map(|_| Arc::default());
+        self
+    }
+
+    // the clock is only looked at every so often, it can be slow to read. so are the other
+    // pieces' nodes when the search is split
+    fn out_of_budget(&mut self) -> Option<SearchStatus> {
+        if self.nodes.is_multiple_of(256) { self.flush(); }
+        let nodes = match &self.spent {
+            Some(spent) => spent.load(atomic::Ordering::Relaxed) + self.nodes - self.flushed,
+            None => self.nodes,
+        };
+
+        if self.max_solutions.is_some_and(|max| self.n_found >= max) { return Some(SearchStatus::SolutionLimit); }
+        if self.max_nodes.is_some_and(|max| nodes >= max) { return Some(SearchStatus::NodeLimit); }
+        if self.cancel.as_ref().is_some_and(CancelFlag::is_cancelled) { return Some(SearchStatus::Cancelled); }
+        if !self.nodes.is_multiple_of(256) { return None; }
+
+        if self.deadline.is_some_and(|deadline| now_ms() >= deadline) { return Some(SearchStatus::Deadline); }
+        None
+    }
 
-            solutions.push(valid_sections);
-            return n_added; 
+    // adds the nodes this search looked at since last time to spent
+    fn flush(&mut self) {
+        let Some(spent) = &self.spent else { return };
+        spent.fetch_add(self.nodes - self.flushed, atomic::Ordering::Relaxed);
+        self.flushed = self.nodes;
+    }
+
+    // None while there may be more to find
+    fn status(&self) -> Option<SearchStatus> {
+        match self.stopped {
+            Some(status) => Some(status),
+            None if self.started && self.stack.len() <= self.floor => Some(SearchStatus::Exhaustive),
+            None => None,
+        }
+    }
+
+    fn result(&self, schedules: Vec<Schedule>) -> SolveResult {
+        SolveResult {
+            schedules,
+            status: self.status().unwrap_or(SearchStatus::Exhaustive),
+            nodes: self.nodes,
+            pruned: self.pruned,
         }
-        
-        if n_considered == self.prefs.lecture_ids.len() { return n_added; }
+    }
 
-        // add the class and add all of its lecture sections
-        mask_values(&self.prefs.lecture_sections[n_added], schedule_mask, true);
-        // exam section
-        if let Some(exam_idx) = self.prefs.exam_sections[n_added] {
-            schedule_mask[exam_idx] = true;
+    // one search for each way of taking or skipping the next course, each starting right below
+    // it. between them they find the same schedules in the same order as this one would have.
+    // also hands back this search, which only did the splitting, for its counts
+    #[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+    fn split(mut self, solver: &BTSolver) -> (Search, Vec<Search>) {
+        let mut branches = Vec::new();
+        if !self.started {
+            self.started = true;
+            if let Step::Prune = self.step(solver, 0, 0, 0.0) { return (self, branches); }
+            self.stack.push(Frame::default());
         }
 
-        for lab_idx in &self.prefs.lab_sections[n_added] {
-            // purpose of sentinel index is to let classes without lab or discussion be considered
-            if *lab_idx != self.prefs.sentinel_idx {
-                // take the lab
-                schedule_mask[*lab_idx] = true;
-                if self.score(&schedule_mask) < 0.0 { // check early instead of backtrack
-                    schedule_mask[*lab_idx] = false;
-                    continue; 
+        let depth = self.stack.len() - 1;
+        let (n_taken, units) = (self.stack[depth].n_taken, self.stack[depth].units);
+        loop {
+            let (n_taken, units) = match self.advance(solver, depth) {
+                Advance::Took(option_units) => (n_taken + 1, units + option_units),
+                Advance::Skipped => (n_taken, units),
+                Advance::Exhausted => break,
+            };
+            // can_split made sure there's a course after this one, so nothing is complete yet
+            if let Step::Prune = self.step(solver, depth + 1, n_taken, units) { continue; }
+
+            let mut branch = self.clone();
+            branch.stack.push(Frame { n_taken, units, ..Default::default() });
+            branch.floor = depth + 1;
+            (branch.nodes, branch.pruned, branch.flushed) = (0, 0, 0);
+            branches.push(branch);
+        }
+
+        self.stack.truncate(self.floor);
+        (self, branches)
+    }
+
+    // whether the course split would decide has another one after it. a search that hasn't
+    // started decides the first course, a piece the one at its floor
+    #[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+    fn can_split(&self) -> bool {
+        self.stack.len().saturating_sub(1) + 1 < self.courses.len()
+    }
+
+    // what to do once depth courses are decided
+    fn step(&mut self, solver: &BTSolver, depth: usize, n_taken: usize, units: f64) -> Step {
+        self.nodes += 1;
+        let step = self.decide(solver, depth, n_taken, units);
+        if let Step::Prune = step { self.pruned += 1; }
+        step
+    }
+
+    fn decide(&self, solver: &BTSolver, depth: usize, n_taken: usize, units: f64) -> Step {
+        let prefs = &solver.prefs;
+
+        // nothing below can score better than what we already have
+        if let Some(threshold) = self.threshold {
+            if solver.bound(&self.schedule_mask, depth) <= threshold { return Step::Prune; }
+        }
+
+        // even taking everything left can't reach the minimum
+        if self.limits {
+            if n_taken + (self.courses.len() - depth) < prefs.min_courses { return Step::Prune; }
+            if units + prefs.units_left[depth] < prefs.min_units { return Step::Prune; }
+        }
+
+        if depth == self.courses.len() { Step::Complete } else { Step::Descend }
+    }
+
+    // moves the course at depth on to its next way of being taken
+    fn advance(&mut self, solver: &BTSolver, depth: usize) -> Advance {
+        let prefs = &solver.prefs;
+        let course_idx = self.courses[depth];
+        let options = &prefs.courses[course_idx];
+        let Search { stack, schedule_mask, limits, .. } = self;
+        let frame = &mut stack[depth];
+        let put_back = |indices: &[usize], schedule_mask: &mut BitSet| indices.iter().for_each(|&idx| schedule_mask.remove(idx));
+
+        loop {
+            if let (Some(option_idx), Some(discussion_idx)) = (frame.option, frame.discussion.take()) {
+                put_back(&options[option_idx].discussions[discussion_idx], schedule_mask);
+            }
+
+            // only the discussions linkedSections allows with this lab
+            if let (Some(option_idx), Some(lab_idx)) = (frame.option, frame.lab) {
+                let option = &options[option_idx];
+                if let Some(&discussion_idx) = option.pairings[lab_idx].get(frame.next_pairing) {
+                    frame.next_pairing += 1;
+                    if solver.take(&option.discussions[discussion_idx], schedule_mask) {
+                        frame.discussion = Some(discussion_idx);
+                        return Advance::Took(option.units);
+                    }
+                    continue;
                 }
+
+                put_back(&option.labs[lab_idx], schedule_mask);
+                frame.lab = None;
             }
 
-            for discussion_idx in &self.prefs.discussion_sections[n_added] {
-                if *discussion_idx != self.prefs.sentinel_idx {
-                    schedule_mask[*discussion_idx] = true;
-                    if self.score(&schedule_mask) < 0.0 { // check early 
-                        schedule_mask[*discussion_idx] = false;
-                        continue; 
+            if let Some(option_idx) = frame.option {
+                let option = &options[option_idx];
+                if let Some(lab) = option.labs.get(frame.next_lab) {
+                    if solver.take(lab, schedule_mask) {
+                        frame.lab = Some(frame.next_lab);
+                        frame.next_pairing = 0;
                     }
+                    frame.next_lab += 1;
+                    continue;
                 }
 
-                // recurse
-                self.search(solutions, n_added+1, n_considered+1, schedule_mask);
+                put_back(&option.required, schedule_mask);
+                frame.option = None;
+            }
 
-                if *discussion_idx != self.prefs.sentinel_idx { schedule_mask[*discussion_idx] = false; }
+            // the next lecture section with all of its lecture meetings and exams.
+            // a required course past the maximum is a dead end
+            if let Some(option) = options.get(frame.next_option) {
+                let over = *limits && (frame.n_taken >= prefs.max_courses || frame.units + option.units > prefs.max_units);
+                if !over && solver.take(&option.required, schedule_mask) {
+                    frame.option = Some(frame.next_option);
+                    frame.next_lab = 0;
+                }
+                frame.next_option += 1;
+                continue;
             }
 
-            if *lab_idx != self.prefs.sentinel_idx { schedule_mask[*lab_idx] = false; }
+            // or leave it out
+            if !frame.skipped && *limits && prefs.optional[course_idx] {
+                frame.skipped = true;
+                return Advance::Skipped;
+            }
+
+            return Advance::Exhausted;
         }
+    }
+
+    // reads the stack as a number whose digits are where each course is among its ways of
+    // being taken, lecture + lab + discussion combinations in the order advance tries them
+    fn progress(&self, solver: &BTSolver) -> Progress {
+        let prefs = &solver.prefs;
+        let mut done = 0.0;
+        let mut scale = 1.0;
+
+        for (depth, frame) in self.stack.iter().enumerate().skip(self.floor) {
+            let course_idx = self.courses[depth];
+            let (position, n_choices) = frame.position(&prefs.courses[course_idx]);
+            let n_choices = n_choices + usize::from(self.limits && prefs.optional[course_idx]);
+            if n_choices == 0 { break; }
 
-        // or don't add it
-        mask_values(&self.prefs.lecture_sections[n_added], schedule_mask, false);
-        if let Some(exam_idx) = self.prefs.exam_sections[n_added] {
-            schedule_mask[exam_idx] = false;
+            done += scale * position as f64 / n_choices as f64;
+            scale /= n_choices as f64;
         }
 
-        // // recurse, missing one course
-        self.search(solutions, n_added, n_considered+1, schedule_mask);
-    
+        let remaining = match self.status() {
+            Some(_) => 0.0,
+            None if !self.started => 1.0,
+            None => (1.0 - done).clamp(0.0, 1.0),
+        };
+        Progress { nodes: self.nodes, found: self.n_found, remaining }
+    }
+
+    // lecture ids of the courses taken so far, courses in the order they were asked for
+    fn lectures(&self, solver: &BTSolver) -> Vec<u64> {
+        let mut taken: Vec<(usize, u64)> = self.stack.iter().zip(&self.courses)
+            .filter_map(|(frame, &course_idx)| frame.option.map(|option_idx| (course_idx, solver.prefs.courses[course_idx][option_idx].lecture_id)))
+            .collect();
+        taken.sort();
+        taken.into_iter().map(|(_, lecture_id)| lecture_id).collect()
+    }
+
+    fn next(&mut self, solver: &BTSolver, report: &mut dyn FnMut(&Progress) -> bool) -> Option<Schedule> {
+        let schedule = self.find_next(solver, report);
+        if schedule.is_some() { self.n_found += 1; }
+        schedule
+    }
+
+    fn find_next(&mut self, solver: &BTSolver, report: &mut dyn FnMut(&Progress) -> bool) -> Option<Schedule> {
+        if !self.started {
+            self.started = true;
+            if let Some(status) = self.out_of_budget() {
+                self.stopped = Some(status);
+                return None;
+            }
+            match self.step(solver, 0, 0, 0.0) {
+                Step::Prune => return None,
+                Step::Complete => return Some(solver.schedule_of(&self.schedule_mask, Vec::new(), 0.0)),
+                Step::Descend => self.stack.push(Frame::default()),
+            }
+        }
+
+        while self.stack.len() > self.floor {
+            let depth = self.stack.len() - 1;
+            if let Some(status) = self.out_of_budget() {
+                self.stopped = Some(status);
+                self.stack.clear();
+                break;
+            }
+            if self.nodes >= self.reported_at + PROGRESS_EVERY {
+                self.reported_at = self.nodes;
+                if !report(&self.progress(solver)) {
+                    self.stopped = Some(SearchStatus::Cancelled);
+                    self.stack.clear();
+                    break;
+                }
+            }
+
+            let (n_taken, units) = (self.stack[depth].n_taken, self.stack[depth].units);
+
+            let (n_taken, units) = match self.advance(solver, depth) {
+                Advance::Took(option_units) => (n_taken + 1, units + option_units),
+                Advance::Skipped => (n_taken, units),
+                Advance::Exhausted => {
+                    self.stack.pop();
+                    continue;
+                }
+            };
 
-        return n_added;
+            match self.step(solver, depth + 1, n_taken, units) {
+                Step::Prune => continue,
+                // left in schedule_mask until the next call
+                Step::Complete => return Some(solver.schedule_of(&self.schedule_mask, self.lectures(solver), units)),
+                Step::Descend => self.stack.push(Frame { n_taken, units, ..Default::default() }),
+            }
+        }
+
+        None
+    }
+}
+
+// iterator over the schedules of a solver, borrowed or owned
+pub struct Schedules<S: Borrow<BTSolver>> {
+    solver: S,
+    search: Search,
+}
+
+impl<S: Borrow<BTSolver>> Schedules<S> {
+    // how the search ended, None while there may be more
+    pub fn status(&self) -> Option<SearchStatus> {
+        self.search.status()
     }
 
-    // assume schedule is always sorted by start date
-    // the purpose of returning a float is to allow incentives for user preferences
-    // probably will add a feature like this if this scheduler actually gets used
-    fn score(&self, schedule_mask: &Vec<bool>) -> f64 {
-        let mut ending = 0; // pretty sure no classes start sunday 12am
-        for section_idx in 0..schedule_mask.len() {
-            if !schedule_mask[section_idx] { continue; }
-            let section = &self.prefs.sections[section_idx];
-            if section.u_start <= ending { return -1.0; }
+    pub fn nodes(&self) -> u64 {
+        self.search.nodes
+    }
+
+    pub fn pruned(&self) -> u64 {
+        self.search.pruned
+    }
+
+    pub fn progress(&self) -> Progress {
+        self.search.progress(self.solver.borrow())
+    }
+}
 
-            ending = section.u_end;
+impl<S: Borrow<BTSolver>> Iterator for Schedules<S> {
+    type Item = Schedule;
+
+    fn next(&mut self) -> Option<Schedule> {
+        self.search.next(self.solver.borrow(), &mut keep_going)
+    }
+}
+
+struct Ranked {
+    // earlier finds win ties, same as the stable sort in solve
+    seq: usize,
+    schedule: Schedule
+}
+
+impl Ord for Ranked {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.schedule.score.total_cmp(&other.schedule.score).then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+impl PartialOrd for Ranked {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Ranked {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Ranked {}
+
+// min heap holding the best k, the worst of them sits on top
+struct TopK {
+    k: usize,
+    n_seen: usize,
+    heap: BinaryHeap<Reverse<Ranked>>
+}
+
+impl TopK {
+    fn add(&mut self, schedule: Schedule) {
+        let seq = self.n_seen;
+        self.n_seen += 1;
+
+        if self.threshold().is_some_and(|threshold| schedule.score <= threshold) { return; }
+
+        self.heap.push(Reverse(Ranked { seq, schedule }));
+        if self.heap.len() > self.k {
+            self.heap.pop();
         }
+    }
 
-        1.0
+    fn threshold(&self) -> Option<f64> {
+        if self.heap.len() < self.k { return None; }
+        self.heap.peek().map(|Reverse(worst)| worst.schedule.score)
     }
 }
 
+// progress reports for searches nobody is watching
+fn keep_going(_: &Progress) -> bool {
+    true
+}
+
+// whether `day` leaves `duration` free seconds between `from` and `to`
+fn has_free_time(day: &[&SectionMeeting], from: u64, to: u64, duration: u64) -> bool {
+    let mut free_since = from;
+    for section in day {
+        if section.u_end <= free_since { continue; }
+        if section.u_start >= to { break; }
+        if section.u_start >= free_since + duration { return true; }
+
+        free_since = section.u_end;
+    }
+
+    to >= free_since + duration
+}
+
 #[cfg(test)]
 mod tests {
     use std::fs;
-    use crate::parse::CourseListContext;
-    use crate::solver::{BTSolver, CoursePreferences};
+    use serde_json::{json, Value};
+    use crate::calendar::Weekday;
+    use crate::constraint::Constraint;
+    use crate::parse::{CourseListContext, CourseRequest, MeetingType, WantedCourse};
+    use crate::solver::{BTSolver, CancelFlag, CoursePreferences, Infeasible, Overlap, Schedule, SchedulePreferences, SearchStatus, SolveOptions, LunchBreak, Progress};
+    use crate::utils::SolveError;
+    use crate::walking::WalkingTimes;
+
+    // bit 1 of inSession is monday
+    const MON: u64 = 1 << 1;
+    const TUE: u64 = 1 << 2;
+    const WED: u64 = 1 << 3;
+
+    fn meeting(in_session: u64, meeting_type: u64, begin: &str, end: &str) -> Value {
+        json!({ "inSession": in_session, "meetingType": meeting_type, "beginTime": begin, "endTime": end })
+    }
+
+    fn class(id: u64, course_number: &str, linked: &[u64], meetings: Vec<Value>) -> Value {
+        let linked: Vec<Value> = linked.iter().map(|id| json!({ "parent": id })).collect();
+        json!({ "id": id, "courseNumber": course_number, "linkedSections": linked, "meetings": meetings })
+    }
+
+    fn catalog(classes: Vec<Value>) -> Value {
+        json!({
+            "meetingTypes": [
+                { "id": 1, "name": "Lecture" },
+                { "id": 2, "name": "Lab" },
+                { "id": 3, "name": "Discussion" },
+                { "id": 4, "name": "Exam" }
+            ],
+            "classes": { "nodes": classes }
+        })
+    }
+
+    // one lecture with an early and a late lab
+    fn early_late_catalog() -> Value {
+        catalog(vec![
+            class(1, "CSE-150-01", &[2, 3], vec![meeting(MON | WED, 1, "1030", "1145")]),
+            class(2, "CSE-150-02L", &[], vec![meeting(MON, 2, "0730", "1020")]),
+            class(3, "CSE-150-03L", &[], vec![meeting(MON, 2, "1200", "1450")]),
+        ])
+    }
+
+    fn lab_names(solver: &BTSolver) -> Vec<String> {
+        solver.solve().iter()
+            .map(|schedule| schedule.sections.iter().find(|s| s.section_id != 1).unwrap().section_name.clone())
+            .collect()
+    }
+
+    #[test]
+    fn score_prefers_later_start() {
+        let gql_response = early_late_catalog();
+        let ctx = CourseListContext::from_value(&gql_response).unwrap();
+        let prefs = CoursePreferences::new(vec![1], ctx).unwrap();
+        let solver = BTSolver::new(prefs).with_preferences(SchedulePreferences {
+            earliest_start: Some(9 * 3600),
+            gap_weight: 0.0,
+            ..Default::default()
+        });
+
+        assert_eq!(lab_names(&solver), vec!["CSE-150-03L", "CSE-150-02L"]);
+
+        // the same from js, where the fields are camelCase like the rest of the api
+        let from_js: SchedulePreferences = serde_json::from_value(json!({ "earliestStart": 9 * 3600, "gapWeight": 0.0 })).unwrap();
+        assert_eq!((from_js.earliest_start, from_js.gap_weight, from_js.early_weight), (Some(9 * 3600), 0.0, 1.0));
+        let lunch: LunchBreak = serde_json::from_value(json!({ "start": 11 * 3600, "end": 14 * 3600, "duration": 1800 })).unwrap();
+        assert_eq!(lunch.duration, 1800);
+    }
+
+    #[test]
+    fn score_keeps_lunch_free() {
+        let gql_response = early_late_catalog();
+        let ctx = CourseListContext::from_value(&gql_response).unwrap();
+        let prefs = CoursePreferences::new(vec![1], ctx).unwrap();
+        let solver = BTSolver::new(prefs).with_preferences(SchedulePreferences {
+            lunch_breaks: vec![LunchBreak { start: 11 * 3600, end: 14 * 3600, duration: 3600 }],
+            gap_weight: 0.0,
+            ..Default::default()
+        });
+
+        assert_eq!(lab_names(&solver), vec!["CSE-150-02L", "CSE-150-03L"]);
+    }
     
+    #[test]
+    fn constraints_prune_sections() {
+        let gql_response = early_late_catalog();
+        let solver_with = |constraints: &[Constraint]| {
+            let ctx = CourseListContext::from_value(&gql_response).unwrap();
+            BTSolver::new(CoursePreferences::new(vec![1], ctx).unwrap().with_constraints(constraints))
+        };
+
+        assert_eq!(lab_names(&solver_with(&[Constraint::EarliestStart { time: 9 * 3600 }])), vec!["CSE-150-03L"]);
+        assert_eq!(lab_names(&solver_with(&[Constraint::LatestEnd { time: 14 * 3600 }])), vec!["CSE-150-02L"]);
+        // the lecture itself meets on monday
+        assert!(solver_with(&[Constraint::BlockedDay { day: Weekday::Monday }]).solve().is_empty());
+        assert_eq!(solver_with(&[]).solve().len(), 2);
+    }
+
+    #[test]
+    fn overlapping_lectures_without_labs() {
+        let gql_response = catalog(vec![
+            class(1, "CSE-150-01", &[], vec![meeting(MON | WED, 1, "1030", "1145")]),
+            class(2, "CSE-175-01", &[], vec![meeting(WED, 1, "1130", "1245")]),
+        ]);
+        let ctx = CourseListContext::from_value(&gql_response).unwrap();
+        let prefs = CoursePreferences::new(vec![1, 2], ctx).unwrap();
+        let solver = BTSolver::new(prefs);
+
+        assert!(solver.solve().is_empty());
+    }
+
+    #[test]
+    fn picks_between_lecture_sections() {
+        let gql_response = catalog(vec![
+            class(1, "CSE-150-01", &[3], vec![meeting(MON | WED, 1, "1030", "1145")]),
+            class(2, "CSE-150-02", &[4, 5], vec![meeting(TUE, 1, "1330", "1445")]),
+            class(3, "CSE-150-03L", &[], vec![meeting(TUE, 2, "0900", "1150")]),
+            // this lab meets twice a week, both meetings come together
+            class(4, "CSE-150-04L", &[], vec![meeting(MON | WED, 2, "0900", "0950")]),
+            class(5, "CSE-150-05L", &[], vec![meeting(WED, 2, "1600", "1650")]),
+            class(6, "CSE-175-01", &[], vec![meeting(MON | WED, 1, "1100", "1215")]),
+        ]);
+        let solve = |want: Vec<CourseRequest>| -> Vec<Vec<String>> {
+            let ctx = CourseListContext::from_value(&gql_response).unwrap();
+            BTSolver::new(CoursePreferences::new(want, ctx).unwrap()).solve().iter()
+                .map(|schedule| schedule.sections.iter().map(|s| s.section_name.clone()).collect())
+                .collect()
+        };
+
+        let alone = solve(vec!["CSE-150".into()]);
+        assert_eq!(alone.len(), 3);
+        assert!(alone.iter().all(|schedule| !schedule.contains(&"CSE-150-03L".into()) || schedule.contains(&"CSE-150-01".into())));
+
+        // section 01 overlaps CSE-175, so only section 02 and its labs are left
+        let both = solve(vec!["CSE-150".into(), 6.into()]);
+        assert_eq!(both.len(), 2);
+        assert!(both.iter().all(|schedule| schedule.contains(&"CSE-150-02".into()) && !schedule.contains(&"CSE-150-01".into())));
+        assert_eq!(both.iter().filter(|schedule| schedule.contains(&"CSE-150-04L".into())).count(), 1);
+    }
+
+    #[test]
+    fn labs_keep_their_discussions() {
+        let gql_response = catalog(vec![
+            class(1, "PHYS-8-01", &[2, 3, 4, 5, 6, 7], vec![meeting(MON | WED, 1, "1030", "1145")]),
+            // 02D only goes with 04L and 03D only with 05L
+            class(2, "PHYS-8-02D", &[4], vec![meeting(TUE, 3, "0900", "0950")]),
+            class(3, "PHYS-8-03D", &[], vec![meeting(TUE, 3, "1000", "1050")]),
+            class(4, "PHYS-8-04L", &[], vec![meeting(WED, 2, "1300", "1550")]),
+            class(5, "PHYS-8-05L", &[3], vec![meeting(MON, 2, "1300", "1550")]),
+            // neither is linked to anything, so they only go with each other
+            class(6, "PHYS-8-06L", &[], vec![meeting(TUE, 2, "1300", "1550")]),
+            class(7, "PHYS-8-07D", &[], vec![meeting(TUE, 3, "1100", "1150")]),
+        ]);
+        let ctx = CourseListContext::from_value(&gql_response).unwrap();
+        let solver = BTSolver::new(CoursePreferences::new(vec![1], ctx).unwrap());
+
+        let mut pairs: Vec<(u64, u64)> = solver.solve().iter().map(|schedule| {
+            let of_type = |t: MeetingType| schedule.sections.iter().find(|s| s.meeting_type == t).unwrap().section_id;
+            (of_type(MeetingType::Lab), of_type(MeetingType::Discussion))
+        }).collect();
+        pairs.sort();
+        assert_eq!(pairs, vec![(4, 2), (5, 3), (6, 7)]);
+    }
+
+    #[test]
+    fn optional_courses_and_course_count() {
+        let gql_response = catalog(vec![
+            class(1, "CSE-150-01", &[], vec![meeting(MON | WED, 1, "1030", "1145")]),
+            class(2, "CSE-175-01", &[], vec![meeting(TUE, 1, "1030", "1145")]),
+            class(3, "ANTH-1-01", &[], vec![meeting(MON, 1, "0900", "0950")]),
+            class(4, "ARTS-7-01", &[], vec![meeting(WED, 1, "1100", "1215")]),
+            class(5, "MUSIC-15-01", &[], vec![meeting(TUE, 1, "1300", "1350")]),
+            class(6, "WRI-10-01", &[], vec![meeting(TUE, 1, "1400", "1450")]),
+        ]);
+        let solve = |min: Option<usize>, max: Option<usize>| -> Vec<Vec<u64>> {
+            let ctx = CourseListContext::from_value(&gql_response).unwrap();
+            let electives = [3.into(), 4.into(), "MUSIC-15".into(), "WRI-10".into()];
+            let mut want: Vec<WantedCourse> = vec![1.into(), 2.into()];
+            want.extend(electives.into_iter().map(CourseRequest::optional));
+
+            let prefs = CoursePreferences::new(want, ctx).unwrap().with_course_count(min, max);
+            BTSolver::new(prefs).solve().iter()
+                .map(|schedule| {
+                    let mut ids: Vec<u64> = schedule.sections.iter().map(|s| s.section_id).collect();
+                    ids.sort();
+                    ids.dedup();
+                    ids
+                })
+                .collect()
+        };
+
+        // ARTS-7 overlaps CSE-150, so three electives are left to pick from
+        let two_electives = solve(Some(4), Some(4));
+        assert_eq!(two_electives.len(), 3);
+        assert!(two_electives.iter().all(|ids| ids.len() == 4 && ids.starts_with(&[1, 2]) && !ids.contains(&4)));
+
+        // anything from just the required courses up to all three electives
+        assert_eq!(solve(None, None).len(), 8);
+        assert_eq!(solve(Some(6), None).len(), 0);
+        // not even room for the required ones
+        assert_eq!(solve(None, Some(1)).len(), 0);
+    }
+
+    #[test]
+    fn unit_range() {
+        let with_units = |id: u64, course_number: &str, units: f64, days: u64| -> Value {
+            let mut lecture = class(id, course_number, &[], vec![meeting(days, 1, &format!("{}00", 8 + id), &format!("{}50", 8 + id))]);
+            lecture["units"] = json!(units);
+            lecture
+        };
+        let gql_response = catalog(vec![
+            with_units(1, "CSE-150-01", 4.0, MON),
+            with_units(2, "CSE-175-01", 4.0, TUE),
+            with_units(3, "PHYS-8-01", 4.0, WED),
+            with_units(4, "SPRT-1-01", 1.0, MON),
+        ]);
+        let solve = |min: Option<f64>, max: Option<f64>| -> Vec<f64> {
+            let ctx = CourseListContext::from_value(&gql_response).unwrap();
+            let want: Vec<WantedCourse> = vec![1.into(), CourseRequest::from(2).optional(),
+                CourseRequest::from(3).optional(), CourseRequest::from(4).optional()];
+            let prefs = CoursePreferences::new(want, ctx).unwrap().with_unit_range(min, max);
+            let mut units: Vec<f64> = BTSolver::new(prefs).solve().iter().map(|schedule| schedule.units).collect();
+            units.sort_by(f64::total_cmp);
+            units
+        };
+
+        assert_eq!(solve(None, None), vec![4.0, 5.0, 8.0, 8.0, 9.0, 9.0, 12.0, 13.0]);
+        assert_eq!(solve(Some(12.0), None), vec![12.0, 13.0]);
+        assert_eq!(solve(Some(8.0), Some(9.0)), vec![8.0, 8.0, 9.0, 9.0]);
+        assert!(solve(Some(14.0), None).is_empty());
+    }
+
+    #[test]
+    fn instructor_preferences() {
+        let taught_by = |id: u64, course_number: &str, begin: &str, end: &str, instructor: &str| -> Value {
+            let mut lecture = class(id, course_number, &[], vec![meeting(MON | WED, 1, begin, end)]);
+            lecture["instructors"] = json!([instructor]);
+            lecture
+        };
+        let gql_response = catalog(vec![
+            taught_by(1, "CSE-150-01", "0800", "0915", "Alan Turing"),
+            taught_by(2, "CSE-150-02", "1100", "1215", "Grace Hopper"),
+            taught_by(3, "CSE-175-01", "1300", "1415", "Alan Turing"),
+        ]);
+        let solver = |instructor_weight: f64, constraints: &[Constraint]| {
+            let ctx = CourseListContext::from_value(&gql_response).unwrap();
+            let prefs = CoursePreferences::new(vec!["CSE-150", "CSE-175"], ctx).unwrap().with_constraints(constraints);
+            BTSolver::new(prefs).with_preferences(SchedulePreferences {
+                earliest_start: Some(10 * 3600),
+                preferred_instructors: vec!["alan turing".into()],
+                instructor_weight,
+                gap_weight: 0.0,
+                ..Default::default()
+            })
+        };
+        let lecture_of = |schedule: &Schedule| schedule.sections[0].section_name.clone();
+
+        // two hours too early on two days costs 4, which one preferred section makes up for
+        let strong = solver(5.0, &[]);
+        assert_eq!(strong.solve().iter().map(|schedule| schedule.score).collect::<Vec<_>>(), vec![10.0 - 4.0, 5.0]);
+        assert_eq!(lecture_of(&strong.solve_top_k(1)[0]), "CSE-150-01");
+        assert_eq!(lecture_of(&solver(1.0, &[]).solve_top_k(1)[0]), "CSE-150-02");
+
+        let avoid = [Constraint::AvoidInstructors { names: vec!["Grace Hopper".into()] }];
+        assert_eq!(solver(1.0, &avoid).solve().iter().map(lecture_of).collect::<Vec<_>>(), vec!["CSE-150-01"]);
+    }
+
+    #[test]
+    fn walking_between_buildings() {
+        let held_in = |id: u64, course_number: &str, begin: &str, end: &str, building: &str| -> Value {
+            let mut lecture = class(id, course_number, &[], vec![meeting(MON, 1, begin, end)]);
+            lecture["meetings"][0]["building"] = json!(building);
+            lecture
+        };
+        let gql_response = catalog(vec![
+            held_in(1, "CSE-150-01", "1030", "1145", "COB"),
+            // five minutes to cross campus
+            held_in(2, "CSE-175-01", "1150", "1305", "SE"),
+            held_in(3, "CSE-175-02", "1400", "1515", "SE"),
+        ]);
+        let walking_times = WalkingTimes::from_csv("COB,SE,420").unwrap();
+        let solve = |walking_times: &WalkingTimes, strict: bool| -> Vec<String> {
+            let ctx = CourseListContext::from_value(&gql_response).unwrap();
+            let prefs = CoursePreferences::new(vec!["CSE-150", "CSE-175"], ctx).unwrap()
+                .with_walking_times(walking_times, strict);
+            BTSolver::new(prefs).with_preferences(SchedulePreferences { gap_weight: 0.0, ..Default::default() })
+                .solve().iter()
+                .map(|schedule| schedule.sections[1].section_name.clone())
+                .collect()
+        };
+
+        assert_eq!(solve(&WalkingTimes::default(), false), vec!["CSE-175-01", "CSE-175-02"]);
+        assert_eq!(solve(&WalkingTimes::default(), true), vec!["CSE-175-01", "CSE-175-02"]);
+        assert_eq!(solve(&walking_times, false), vec!["CSE-175-02", "CSE-175-01"]);
+        assert_eq!(solve(&walking_times, true), vec!["CSE-175-02"]);
+
+        let endless = WalkingTimes::from_csv(&format!("COB,SE,{}", u64::MAX)).unwrap();
+        assert!(solve(&endless, true).is_empty());
+    }
+
+    #[test]
+    fn explains_why_nothing_fits() {
+        let mut classes = vec![
+            class(1, "CSE-150-01", &[], vec![meeting(TUE, 1, "1330", "1445")]),
+            class(2, "CSE-175-01", &[3, 4], vec![meeting(MON, 1, "0900", "1015")]),
+            class(3, "CSE-175-02L", &[], vec![meeting(TUE, 2, "1300", "1550")]),
+            class(4, "CSE-175-03L", &[], vec![meeting(TUE, 2, "1400", "1650")]),
+            class(5, "ANTH-1-01", &[], vec![meeting(WED, 1, "0900", "0950")]),
+        ];
+        classes[0]["units"] = json!(4);
+        let gql_response = catalog(classes);
+        let solver = |want: Vec<&str>, constraints: &[Constraint]| {
+            let ctx = CourseListContext::from_value(&gql_response).unwrap();
+            let prefs = CoursePreferences::new(want, ctx).unwrap().with_constraints(constraints);
+            BTSolver::new(prefs)
+        };
+
+        let clash = solver(vec!["ANTH-1", "CSE-150", "CSE-175"], &[]);
+        assert!(clash.solve().is_empty());
+        assert_eq!(clash.explain_infeasible(), Some(Infeasible::Clash {
+            courses: vec!["CSE-150".into(), "CSE-175".into()],
+            overlaps: vec![Overlap {
+                section: "CSE-150-01 Lecture TUE 1:30 PM-2:45 PM".into(),
+                with: vec!["CSE-175-02L".into(), "CSE-175-03L".into()]
+            }]
+        }));
+
+        assert_eq!(solver(vec!["ANTH-1", "CSE-150"], &[Constraint::BlockedDay { day: Weekday::Tuesday }]).explain_infeasible(),
+            Some(Infeasible::NoValidSection { course: "CSE-150".into() }));
+        assert_eq!(solver(vec!["ANTH-1", "CSE-150"], &[]).explain_infeasible(), None);
+
+        let ctx = CourseListContext::from_value(&gql_response).unwrap();
+        let heavy = CoursePreferences::new(vec!["ANTH-1", "CSE-150"], ctx).unwrap().with_unit_range(Some(12.0), None);
+        assert_eq!(BTSolver::new(heavy).explain_infeasible(), Some(Infeasible::OutsideLimits));
+
+        // the explanation keeps to the budget it's given
+        let few_nodes = SolveOptions { max_nodes: Some(1), ..Default::default() };
+        assert_eq!(clash.explain_infeasible_with(&few_nodes), None);
+        assert_eq!(clash.explain_infeasible_with(&SolveOptions { deadline: Some(0.0), ..Default::default() }), None);
+        assert_eq!(clash.explain_infeasible_with(&SolveOptions::default()), clash.explain_infeasible());
+        assert_eq!(few_nodes.remaining(5, 0.0).max_nodes, Some(0));
+    }
+
+    #[test]
+    fn exams_only_clash_on_the_same_date() {
+        let with_exam = |id: u64, course_number: &str, days: u64, date: &str| -> Value {
+            let mut lecture = class(id, course_number, &[], vec![meeting(days, 1, "0900", "0950")]);
+            lecture["meetings"].as_array_mut().unwrap().push(json!({
+                "inSession": TUE, "meetingType": 4, "beginTime": "0800", "endTime": "1100",
+                "beginDate": date, "endDate": date
+            }));
+            lecture
+        };
+        let n_schedules = |dates: [&str; 2]| -> usize {
+            let gql_response = catalog(vec![
+                with_exam(1, "CSE-150-01", MON, dates[0]),
+                with_exam(2, "CSE-175-01", WED, dates[1]),
+                // weekly tuesday lecture during the exams' time slot
+                class(3, "CSE-185-01", &[], vec![meeting(TUE, 1, "0900", "0950")]),
+            ]);
+            let ctx = CourseListContext::from_value(&gql_response).unwrap();
+            BTSolver::new(CoursePreferences::new(vec![1, 2, 3], ctx).unwrap()).solve().len()
+        };
+
+        assert_eq!(n_schedules(["2024-12-10", "2024-12-17"]), 1);
+        assert_eq!(n_schedules(["2024-12-10", "2024-12-10"]), 0);
+    }
+
+    #[test]
+    fn top_k_matches_solve() {
+        // three courses with four labs each, some of them overlapping across courses
+        let mut classes = Vec::new();
+        for course in 0..3u64 {
+            let lecture_id = course * 10;
+            let lab_ids: Vec<u64> = (1..5).map(|lab| lecture_id + lab).collect();
+            classes.push(class(lecture_id, &format!("CSE-{}-01", course), &lab_ids,
+                vec![meeting(MON | WED, 1, &format!("{:0>2}00", 8 + 2 * course), &format!("{:0>2}50", 8 + 2 * course))]));
+            for (i, lab_id) in lab_ids.iter().enumerate() {
+                let hour = 13 + i as u64 + course;
+                classes.push(class(*lab_id, &format!("CSE-{}-0{}L", course, i + 2), &[],
+                    vec![meeting(1 << (2 + i as u64 % 2), 2, &format!("{:0>2}00", hour), &format!("{:0>2}50", hour))]));
+            }
+        }
+        let gql_response = catalog(classes);
+        let ctx = CourseListContext::from_value(&gql_response).unwrap();
+        let prefs = CoursePreferences::new(vec![0, 10, 20], ctx).unwrap();
+        let solver = BTSolver::new(prefs).with_preferences(SchedulePreferences {
+            latest_end: Some(15 * 3600),
+            ..Default::default()
+        });
+
+        let all: Vec<String> = solver.solve().iter().map(|v| format!("{:?}", v)).collect();
+        assert!(all.len() > 5);
+        for k in [1, 3, 5, all.len(), all.len() + 2] {
+            let top: Vec<String> = solver.solve_top_k(k).iter().map(|v| format!("{:?}", v)).collect();
+            assert_eq!(top, all[..k.min(all.len())]);
+        }
+        assert!(solver.solve_top_k(0).is_empty());
+    }
+
+    #[test]
+    fn split_search_matches_serial() {
+        let mut classes = Vec::new();
+        for course in 0..3u64 {
+            let lecture_id = course * 10;
+            let lab_ids: Vec<u64> = (1..4).map(|lab| lecture_id + lab).collect();
+            classes.push(class(lecture_id, &format!("CSE-{}-01", course), &lab_ids,
+                vec![meeting(MON | WED, 1, &format!("{:0>2}00", 8 + 2 * course), &format!("{:0>2}50", 8 + 2 * course))]));
+            for (i, lab_id) in lab_ids.iter().enumerate() {
+                let hour = 13 + i as u64;
+                classes.push(class(*lab_id, &format!("CSE-{}-0{}L", course, i + 2), &[],
+                    vec![meeting(1 << (1 + (course + i as u64) % 3), 2, &format!("{:0>2}00", hour), &format!("{:0>2}50", hour))]));
+            }
+        }
+        let gql_response = catalog(classes);
+        let ctx = CourseListContext::from_value(&gql_response).unwrap();
+        // the first course being optional gives a branch that skips it
+        let want = vec![CourseRequest::Lecture(0).optional(), 10.into(), 20.into()];
+        let solver = BTSolver::new(CoursePreferences::new(want, ctx).unwrap()).with_preferences(SchedulePreferences {
+            latest_end: Some(14 * 3600),
+            ..Default::default()
+        });
+
+        // iter never splits
+        let mut serial = solver.iter();
+        let mut expected: Vec<Schedule> = serial.by_ref().collect();
+        expected.sort_by(|a, b| b.score.total_cmp(&a.score));
+        let expected: Vec<String> = expected.iter().map(|v| format!("{:?}", v)).collect();
+        assert!(expected.len() > 5);
+
+        let res = solver.solve_with(&SolveOptions::default());
+        assert_eq!(res.schedules.iter().map(|v| format!("{:?}", v)).collect::<Vec<_>>(), expected);
+        assert_eq!((res.status, res.nodes, res.pruned), (SearchStatus::Exhaustive, serial.nodes(), serial.pruned()));
+
+        // without the parallel feature everything above ran serially, so check the splitting
+        // itself goes below the first course
+        #[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+        {
+            let (_, first) = solver.search(&SolveOptions::default()).split(&solver);
+            assert_eq!(first.len(), 4);
+            let (_, second) = first.into_iter().next().unwrap().split(&solver);
+            assert!(second.len() > 1 && !second.iter().any(crate::solver::Search::can_split));
+        }
+
+        // big enough node budgets split too, they're shared between the pieces
+        let limited = solver.solve_with(&SolveOptions { max_nodes: Some(1 << 16), ..Default::default() });
+        assert_eq!((limited.schedules.len(), limited.status), (expected.len(), SearchStatus::Exhaustive));
+        // solution budgets don't, it's always the first ones found
+        let budget = SolveOptions { max_solutions: Some(3), ..Default::default() };
+        let mut first: Vec<Schedule> = solver.iter_with(&budget).collect();
+        first.sort_by(|a, b| b.score.total_cmp(&a.score));
+        let three = solver.solve_with(&budget);
+        assert_eq!(three.schedules.iter().map(|v| format!("{:?}", v)).collect::<Vec<_>>(),
+            first.iter().map(|v| format!("{:?}", v)).collect::<Vec<_>>());
+        assert_eq!((three.schedules.len(), three.status), (3, SearchStatus::SolutionLimit));
+
+        for k in [1, 4, expected.len() + 1] {
+            let top: Vec<String> = solver.solve_top_k(k).iter().map(|v| format!("{:?}", v)).collect();
+            assert_eq!(top, expected[..k.min(expected.len())]);
+        }
+    }
+
+    #[test]
+    fn iter_resumes_where_it_left_off() {
+        let gql_response = catalog(vec![
+            class(1, "CSE-150-01", &[3, 4], vec![meeting(MON | WED, 1, "0900", "1015")]),
+            class(2, "CSE-150-02", &[3, 4], vec![meeting(TUE, 1, "0900", "1015")]),
+            class(3, "CSE-150-03L", &[], vec![meeting(MON, 2, "1300", "1550")]),
+            class(4, "CSE-150-04L", &[], vec![meeting(TUE, 2, "1300", "1550")]),
+            class(5, "CSE-175-01", &[], vec![meeting(MON | WED, 1, "1030", "1145")]),
+            class(6, "CSE-175-02", &[], vec![meeting(TUE, 1, "0930", "1045")]),
+        ]);
+        let ctx = CourseListContext::from_value(&gql_response).unwrap();
+        let solver = BTSolver::new(CoursePreferences::new(vec!["CSE-150", "CSE-175"], ctx).unwrap());
+        let names = |schedule: &Schedule| -> Vec<String> {
+            schedule.sections.iter().map(|section| section.section_name.clone()).collect()
+        };
+
+        let mut schedules = solver.iter();
+        let mut found: Vec<Vec<String>> = schedules.by_ref().take(2).map(|schedule| names(&schedule)).collect();
+        assert_eq!(found.len(), 2);
+        found.extend(schedules.map(|schedule| names(&schedule)));
+
+        let mut solved: Vec<Vec<String>> = solver.solve().iter().map(names).collect();
+        assert_eq!(solved.len(), 6);
+        found.sort();
+        solved.sort();
+        assert_eq!(found, solved);
+
+        let mut owned = solver.into_schedules(&SolveOptions::default());
+        assert_eq!(owned.by_ref().count(), 6);
+        assert!(owned.next().is_none());
+    }
+
+    #[test]
+    fn budgets_stop_the_search() {
+        let gql_response = catalog(vec![
+            class(1, "CSE-150-01", &[3, 4], vec![meeting(MON | WED, 1, "0900", "1015")]),
+            class(2, "CSE-150-02", &[3, 4], vec![meeting(TUE, 1, "0900", "1015")]),
+            class(3, "CSE-150-03L", &[], vec![meeting(MON, 2, "1300", "1550")]),
+            class(4, "CSE-150-04L", &[], vec![meeting(TUE, 2, "1300", "1550")]),
+            class(5, "CSE-175-01", &[], vec![meeting(MON | WED, 1, "1030", "1145")]),
+        ]);
+        let ctx = CourseListContext::from_value(&gql_response).unwrap();
+        let solver = BTSolver::new(CoursePreferences::new(vec!["CSE-150", "CSE-175"], ctx).unwrap());
+
+        let all = solver.solve_with(&SolveOptions::default());
+        assert_eq!((all.schedules.len(), all.status), (4, SearchStatus::Exhaustive));
+        assert!(all.nodes > 4);
+
+        let two = solver.solve_with(&SolveOptions { max_solutions: Some(2), ..Default::default() });
+        assert_eq!((two.schedules.len(), two.status), (2, SearchStatus::SolutionLimit));
+        let best = solver.solve_top_k_with(1, &SolveOptions { max_solutions: Some(2), ..Default::default() });
+        assert_eq!((best.schedules.len(), best.status), (1, SearchStatus::SolutionLimit));
+
+        let few_nodes = solver.solve_with(&SolveOptions { max_nodes: Some(2), ..Default::default() });
+        assert_eq!((few_nodes.nodes, few_nodes.status), (2, SearchStatus::NodeLimit));
+
+        let cancel = CancelFlag::new();
+        cancel.cancel();
+        let cancelled = solver.solve_with(&SolveOptions { cancel: Some(cancel), ..Default::default() });
+        assert_eq!((cancelled.schedules.len(), cancelled.status), (0, SearchStatus::Cancelled));
+        assert_eq!(solver.solve_with(&SolveOptions { deadline: Some(0.0), ..Default::default() }).status, SearchStatus::Deadline);
+
+        let mut schedules = solver.iter_with(&SolveOptions { max_solutions: Some(3), ..Default::default() });
+        assert_eq!(schedules.by_ref().take(3).count(), 3);
+        assert_eq!(schedules.status(), None);
+        assert!(schedules.next().is_none());
+        assert_eq!(schedules.status(), Some(SearchStatus::SolutionLimit));
+
+        // a stream stops at the next call after the flag is set
+        let cancel = CancelFlag::new();
+        let mut schedules = solver.iter_with(&SolveOptions { cancel: Some(cancel.clone()), ..Default::default() });
+        assert!(schedules.next().is_some());
+        cancel.cancel();
+        assert!(schedules.next().is_none());
+        assert_eq!(schedules.status(), Some(SearchStatus::Cancelled));
+    }
+
+    #[test]
+    fn reports_progress() {
+        // four courses with twelve interchangeable labs each, every combination fits
+        let mut classes = Vec::new();
+        for course in 0..4u64 {
+            let lecture_id = course * 100;
+            let lab_ids: Vec<u64> = (1..=12).map(|lab| lecture_id + lab).collect();
+            classes.push(class(lecture_id, &format!("CSE-{}-01", course), &lab_ids,
+                vec![meeting(MON, 1, &format!("{:0>2}00", 8 + course), &format!("{:0>2}50", 8 + course))]));
+            for (i, lab_id) in lab_ids.iter().enumerate() {
+                classes.push(class(*lab_id, &format!("CSE-{}-{:0>2}L", course, i + 2), &[],
+                    vec![meeting(TUE, 2, &format!("{:0>2}00", 8 + 2 * course), &format!("{:0>2}50", 8 + 2 * course))]));
+            }
+        }
+        let gql_response = catalog(classes);
+        let ctx = CourseListContext::from_value(&gql_response).unwrap();
+        let solver = BTSolver::new(CoursePreferences::new(vec![0, 100, 200, 300], ctx).unwrap());
+
+        let mut schedules = solver.iter();
+        assert_eq!(schedules.progress().remaining, 1.0);
+        schedules.by_ref().take(12 * 12 * 12).count();
+        let one_twelfth = schedules.progress();
+        assert_eq!(one_twelfth.found, 12 * 12 * 12);
+        assert!(one_twelfth.remaining > 0.9 && one_twelfth.remaining < 1.0, "{:?}", one_twelfth);
+        schedules.by_ref().count();
+        assert_eq!(schedules.progress().remaining, 0.0);
+
+        let mut reports = Vec::new();
+        let res = solver.solve_with_progress(&SolveOptions::default(), |progress| {
+            reports.push(*progress);
+            true
+        });
+        assert_eq!(res.schedules.len(), 12 * 12 * 12 * 12);
+        assert!(reports.len() > 1);
+        assert!(reports.windows(2).all(|pair| pair[0].remaining >= pair[1].remaining && pair[0].found <= pair[1].found));
+        assert_eq!(*reports.last().unwrap(), Progress { nodes: res.nodes, found: res.schedules.len(), remaining: 0.0 });
+
+        // stop at the first report
+        let res = solver.solve_with_progress(&SolveOptions::default(), |_| false);
+        assert_eq!(res.status, SearchStatus::Cancelled);
+        assert!(res.schedules.len() < 12 * 12 * 12 * 12);
+    }
+
+    #[test]
+    fn repeated_requests_are_merged() {
+        // an online lecture has no meetings, so asking for it twice can't clash with itself
+        let gql_response = catalog(vec![
+            class(1, "CSE-5-01", &[], vec![]),
+            class(5, "CSE-175-01", &[], vec![meeting(MON | WED, 1, "1030", "1145")]),
+        ]);
+        let ctx = CourseListContext::from_value(&gql_response).unwrap();
+        let want: Vec<WantedCourse> = vec![CourseRequest::from(1).optional(), 1.into(), 5.into()];
+        let solver = BTSolver::new(CoursePreferences::new(want, ctx).unwrap());
+
+        assert_eq!(solver.solve().len(), 1);
+        assert_eq!(solver.iter().count(), 1);
+        assert_eq!(solver.solve_top_k(3).len(), 1);
+
+        // the code and the id are the same one section, so it's two courses
+        let ctx = CourseListContext::from_value(&gql_response).unwrap();
+        let want: Vec<WantedCourse> = vec!["cse-175".into(), 5.into(), 1.into()];
+        let prefs = CoursePreferences::new(want, ctx).unwrap().with_course_count(Some(2), Some(2));
+        assert_eq!(BTSolver::new(prefs).solve().len(), 1);
+    }
+
+    #[test]
+    fn requests_sharing_a_lecture_are_one_course() {
+        let gql_response = catalog(vec![
+            class(1, "CSE-150-01", &[], vec![meeting(MON | WED, 1, "0900", "1015")]),
+            class(2, "CSE-150-02", &[], vec![meeting(TUE, 1, "0900", "1015")]),
+            class(3, "CSE-150-03", &[], vec![meeting(MON | WED, 1, "1300", "1415")]),
+        ]);
+        let ctx = CourseListContext::from_value(&gql_response).unwrap();
+        let lectures_of = |want: Vec<WantedCourse>| -> Vec<Vec<u64>> {
+            BTSolver::new(CoursePreferences::new(want, &ctx).unwrap()).solve().into_iter().map(|schedule| schedule.lectures).collect()
+        };
+
+        // the section has to be the one asked for by id
+        assert_eq!(lectures_of(vec!["CSE-150".into(), 1.into()]), vec![vec![1]]);
+        assert_eq!(lectures_of(vec![CourseRequest::from("CSE-150").optional(), 2.into()]), vec![vec![2]]);
+        // nothing required, any of them or none
+        assert_eq!(lectures_of(vec![CourseRequest::from(1).optional(), CourseRequest::from("CSE-150").optional()]),
+            vec![vec![1], vec![2], vec![3], vec![]]);
+
+        // two different sections are two courses, unless a request ties them together
+        assert_eq!(lectures_of(vec![1.into(), 2.into()]), vec![vec![1, 2]]);
+        let err = CoursePreferences::new(vec![WantedCourse::from(1), 2.into(), "CSE-150".into()], &ctx).unwrap_err();
+        assert!(matches!(err, SolveError::InvalidPreferences { .. }), "{}", err);
+    }
+
+    #[test]
+    fn overlapping_requests_give_unique_schedules() {
+        let gql_response = catalog(vec![
+            class(1, "CSE-5-01", &[], vec![]),
+            class(2, "CSE-5-02", &[], vec![]),
+            class(3, "CSE-150-01", &[5, 6], vec![meeting(MON | WED, 1, "0900", "1015")]),
+            class(4, "CSE-150-02", &[5, 6], vec![meeting(TUE, 1, "0900", "1015")]),
+            class(5, "CSE-150-03L", &[], vec![meeting(MON, 2, "1300", "1550")]),
+            class(6, "CSE-150-04L", &[], vec![meeting(TUE, 2, "1300", "1550")]),
+        ]);
+        let ctx = CourseListContext::from_value(&gql_response).unwrap();
+        // none of these ask for exactly the same sections twice
+        let wants: Vec<(Vec<WantedCourse>, usize)> = vec![
+            (vec![CourseRequest::from("CSE-5").optional(), 1.into()], 1),
+            (vec![CourseRequest::from("CSE-5").optional(), CourseRequest::from(2).optional(), "CSE-150".into()], 3 * 4),
+            (vec![CourseRequest::from(3).optional(), CourseRequest::from("cse-150").optional(), 4.into(), "CSE-5".into()], 2 * 2),
+        ];
+        for (want, n_schedules) in wants {
+            let solver = BTSolver::new(CoursePreferences::new(want.clone(), &ctx).unwrap());
+            let keys: Vec<Vec<u64>> = solver.solve().iter().map(Schedule::section_ids).collect();
+            let unique: std::collections::HashSet<&Vec<u64>> = keys.iter().collect();
+            assert_eq!((keys.len(), unique.len()), (n_schedules, n_schedules), "{:?}", want);
+            assert_eq!(solver.iter().count(), n_schedules);
+            assert_eq!(solver.solve_top_k(n_schedules + 1).len(), n_schedules);
+        }
+    }
+
+    #[test]
+    fn online_sections_are_listed() {
+        let gql_response = catalog(vec![
+            class(1, "CSE-5-01", &[], vec![]),
+            class(2, "CSE-5-02", &[], vec![]),
+        ]);
+        let ctx = CourseListContext::from_value(&gql_response).unwrap();
+        let schedules = BTSolver::new(CoursePreferences::new(vec!["CSE-5"], ctx).unwrap()).solve();
+
+        assert!(schedules.iter().all(|schedule| schedule.sections.is_empty()));
+        assert_eq!(schedules.iter().map(Schedule::section_ids).collect::<Vec<_>>(), vec![vec![1], vec![2]]);
+        let value = serde_json::to_value(&schedules[0]).unwrap();
+        assert_eq!(value["lectures"], json!([1]));
+    }
+
+    #[test]
+    fn fixture_schedules_are_unique() {
+        let res = fs::read("data/mess.json");
+        let gql_response: Value = serde_json::from_str(std::str::from_utf8(&res.unwrap()).unwrap()).unwrap();
+        let ctx = CourseListContext::from_value(&gql_response).unwrap();
+        let mine = vec![2023337427, 2023337795, 2023336415, 2023337412];
+        let wants = [
+            vec![2023337427],
+            mine.clone(),
+            [mine.clone(), vec![2023330086]].concat(),
+            [mine.clone(), vec![2023335669]].concat(),
+            vec![2023333322],
+        ];
+        for want in wants {
+            let solver = BTSolver::new(CoursePreferences::new(want.clone(), &ctx).unwrap());
+            let keys: Vec<Vec<u64>> = solver.solve().iter().map(Schedule::section_ids).collect();
+            let unique: std::collections::HashSet<&Vec<u64>> = keys.iter().collect();
+            assert_eq!(unique.len(), keys.len(), "{:?}", want);
+        }
+    }
+
     #[test]
     fn score_1_class() {
         let res = fs::read("data/mess.json");
         let gql_response = serde_json::from_str(std::str::from_utf8(&res.unwrap()).unwrap()).unwrap();
         let want = vec![2023337427];
-        let ctx = CourseListContext::new(&gql_response).unwrap();
+        let ctx = CourseListContext::from_value(&gql_response).unwrap();
         let prefs = CoursePreferences::new(want, ctx).unwrap();
         let solver = BTSolver::new(prefs);
         let res = solver.solve();
@@ -213,7 +2110,7 @@ mod tests {
         let res = fs::read("data/mess.json");
         let gql_response = serde_json::from_str(std::str::from_utf8(&res.unwrap()).unwrap()).unwrap();
         let want = vec![2023337427];
-        let ctx = CourseListContext::new(&gql_response).unwrap();
+        let ctx = CourseListContext::from_value(&gql_response).unwrap();
         let prefs = CoursePreferences::new(want, ctx).unwrap();
         let solver = BTSolver::new(prefs);
         let res = solver.solve();
@@ -226,13 +2123,13 @@ mod tests {
         let res = fs::read("data/mess.json");
         let gql_response = serde_json::from_str(std::str::from_utf8(&res.unwrap()).unwrap()).unwrap();
         let want = vec![2023337427, 2023337795,  2023336415, 2023337412];
-        let ctx = CourseListContext::new(&gql_response).unwrap();
+        let ctx = CourseListContext::from_value(&gql_response).unwrap();
         let prefs = CoursePreferences::new(want, ctx).unwrap();
         // prefs.sections.iter().for_each(|a| { println!("{:?}", a) });
         let solver = BTSolver::new(prefs);
         let res = solver.solve();
         res.iter().for_each(|v| { 
-            v.iter().for_each(|a| { println!("{:?}", a) });
+            v.sections.iter().for_each(|a| { println!("{:?}", a) });
             println!();
         });
         // println!("{:?}", v["data"]["classes"]["nodes"].as_array().unwrap().len())
@@ -244,7 +2141,7 @@ mod tests {
         let gql_response = serde_json::from_str(std::str::from_utf8(&res.unwrap()).unwrap()).unwrap();
         let want = vec![2023333322];
         // let want = vec![2023337427, 2023337795,  2023336415, 2023337412, 2023333322];
-        let ctx = CourseListContext::new(&gql_response).unwrap();
+        let ctx = CourseListContext::from_value(&gql_response).unwrap();
         let prefs = CoursePreferences::new(want, ctx).unwrap();
         let solver = BTSolver::new(prefs);
         let res = solver.solve();
@@ -256,12 +2153,12 @@ mod tests {
         let res = fs::read("data/mess.json");
         let gql_response = serde_json::from_str(std::str::from_utf8(&res.unwrap()).unwrap()).unwrap();
         let want = vec![2023337427, 2023337795,  2023336415, 2023337412, 2023330086];
-        let ctx = CourseListContext::new(&gql_response).unwrap();
+        let ctx = CourseListContext::from_value(&gql_response).unwrap();
         let prefs = CoursePreferences::new(want, ctx).unwrap();
         let solver = BTSolver::new(prefs);
         let res = solver.solve();
         res.iter().for_each(|v| { 
-            v.iter().for_each(|a| { println!("{:?}", a) });
+            v.sections.iter().for_each(|a| { println!("{:?}", a) });
             println!();
         });
     }
@@ -271,12 +2168,12 @@ mod tests {
         let res = fs::read("data/mess.json");
         let gql_response = serde_json::from_str(std::str::from_utf8(&res.unwrap()).unwrap()).unwrap();
         let want = vec![2023337427, 2023337795,  2023336415, 2023337412, 2023335669];
-        let ctx = CourseListContext::new(&gql_response).unwrap();
+        let ctx = CourseListContext::from_value(&gql_response).unwrap();
         let prefs = CoursePreferences::new(want, ctx).unwrap();
         let solver = BTSolver::new(prefs);
         let res = solver.solve();
         res.iter().for_each(|v| { 
-            v.iter().for_each(|a| { println!("{:?}", a) });
+            v.sections.iter().for_each(|a| { println!("{:?}", a) });
             println!();
         });
     }
@@ -0,0 +1,216 @@
+use wasm_bindgen::prelude::*;
+
+// what the exported functions take and return, written by hand to match the serde shapes in
+// model, parse, constraint and solver. keep them in sync when those change.
+// everything that can fail throws a SolveError object
+#[wasm_bindgen(typescript_custom_section)]
+const TS_TYPES: &'static str = r#"
+export type Weekday = "Sunday" | "Monday" | "Tuesday" | "Wednesday" | "Thursday" | "Friday" | "Saturday";
+
+export type MeetingType = "Lecture" | "Discussion" | "Lab" | "Exam" | "Other";
+
+// the graphql response's data object
+export interface Catalog {
+    meetingTypes: { id: number; name: string }[];
+    classes: { nodes: ClassNode[] };
+}
+
+export interface ClassNode {
+    id: number;
+    courseNumber: string;
+    units?: number | null;
+    enrollment?: number | null;
+    capacity?: number | null;
+    waitlist?: number | null;
+    instructors?: string[];
+    linkedSections: { parent: number }[];
+    meetings: Meeting[];
+}
+
+export interface Meeting {
+    // bit i set means the meeting happens on day i, sunday first
+    inSession: number;
+    meetingType: number;
+    // "1330" style
+    beginTime?: string | null;
+    endTime?: string | null;
+    // "2024-12-10" style
+    beginDate?: string | null;
+    endDate?: string | null;
+    building?: string | null;
+    room?: string | null;
+}
+
+export interface SectionMeeting {
+    // seconds since sunday 12am
+    u_start: number;
+    u_end: number;
+    section_id: number;
+    lecture_id: number;
+    section_name: string;
+    meeting_type: MeetingType;
+    start_date?: string | null;
+    end_date?: string | null;
+    open_seats?: number | null;
+    waitlist?: number | null;
+    instructors: string[];
+    building?: string | null;
+    room?: string | null;
+}
+
+export interface Schedule {
+    // sorted by start
+    sections: SectionMeeting[];
+    // lecture section id taken for each course, online ones have no meetings
+    lectures: number[];
+    units: number;
+    // higher is better
+    score: number;
+}
+
+export type SearchStatus = "exhaustive" | "solutionLimit" | "nodeLimit" | "deadline" | "cancelled";
+
+export interface SolveResult {
+    // best first
+    schedules: Schedule[];
+    status: SearchStatus;
+    nodes: number;
+    pruned: number;
+}
+
+export interface Progress {
+    nodes: number;
+    // schedules found so far
+    found: number;
+    // rough share of the search still to go, 1 down to 0
+    remaining: number;
+}
+
+// called every so often during a solve and once at the end, returning false stops the search
+export type ProgressCallback = (progress: Progress) => boolean | void;
+
+export interface SolveOptions {
+    maxSolutions?: number | null;
+    maxNodes?: number | null;
+    // milliseconds
+    deadline?: number | null;
+    onProgress?: ProgressCallback;
+}
+
+// a lecture id or a course code like "CSE-150"
+export type CourseRequest = number | string;
+
+export type WantedCourse = CourseRequest | { course: CourseRequest; optional?: boolean };
+
+export type Want = WantedCourse[] | {
+    courses: WantedCourse[];
+    minCourses?: number | null;
+    maxCourses?: number | null;
+    minUnits?: number | null;
+    maxUnits?: number | null;
+};
+
+// times are seconds after midnight
+export interface LunchBreak {
+    start: number;
+    end: number;
+    duration: number;
+}
+
+// times are seconds after midnight, penalties are per hour
+export interface SchedulePreferences {
+    earliestStart?: number | null;
+    latestEnd?: number | null;
+    daysOff?: Weekday[];
+    lunchBreaks?: LunchBreak[];
+    preferredInstructors?: string[];
+    earlyWeight?: number;
+    lateWeight?: number;
+    dayOffWeight?: number;
+    gapWeight?: number;
+    lunchWeight?: number;
+    instructorWeight?: number;
+    walkingWeight?: number;
+}
+
+// times are seconds after midnight
+export type Constraint =
+    | { kind: "blockedInterval"; day?: Weekday | null; start: number; end: number }
+    | { kind: "blockedDay"; day: Weekday }
+    | { kind: "earliestStart"; time: number }
+    | { kind: "latestEnd"; time: number }
+    | { kind: "openSeats"; maxWaitlist?: number | null }
+    | { kind: "avoidInstructors"; names: string[] };
+
+// seconds, { "COB": { "SE": 420 } }
+export type WalkingTimes = Record<string, Record<string, number>>;
+
+// strict rules out transitions that are too short instead of penalizing them
+export type Walking = WalkingTimes | { times: WalkingTimes; strict: boolean };
+
+export interface Overlap {
+    section: string;
+    with: string[];
+}
+
+export type Infeasible =
+    | { kind: "noValidSection"; course: string }
+    | { kind: "clash"; courses: string[]; overlaps: Overlap[] }
+    | { kind: "outsideLimits" };
+
+export type SolveError = { message: string } & (
+    | { kind: "invalidCatalog"; path: string; reason: string }
+    | { kind: "invalidPreferences"; reason: string }
+    | { kind: "invalidSchedule"; reason: string }
+    | { kind: "missingCourse"; id: number }
+    | { kind: "unknownCourse"; code: string }
+    | { kind: "missingSection"; lectureId: number; sectionId: number }
+    | { kind: "badTime"; sectionId: number; field: string; raw?: string | null }
+    | { kind: "badDate"; sectionId: number; field: string; raw: string }
+    | { kind: "missingDates"; sectionId: number }
+    | { kind: "unknownMeetingType"; id: number }
+    | { kind: "invalidWalkingTimes"; reason: string }
+    | { kind: "noSchedule"; explanation: Infeasible }
+    | { kind: "serialization"; reason: string }
+);
+"#;
+
+// the same types on the rust side of the signatures, only checked by typescript
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "Catalog")]
+    pub type JsCatalog;
+    #[wasm_bindgen(typescript_type = "Want")]
+    pub type JsWant;
+    #[wasm_bindgen(typescript_type = "SchedulePreferences | undefined")]
+    pub type JsSchedulePreferences;
+    #[wasm_bindgen(typescript_type = "Constraint[] | undefined")]
+    pub type JsConstraints;
+    #[wasm_bindgen(typescript_type = "Walking | undefined")]
+    pub type JsWalkingTimes;
+    #[wasm_bindgen(typescript_type = "SolveOptions | undefined")]
+    pub type JsSolveOptions;
+    // throws when options is undefined
+    #[wasm_bindgen(method, getter, js_name = onProgress)]
+    pub fn on_progress(this: &JsSolveOptions) -> JsProgressCallback;
+    #[wasm_bindgen(typescript_type = "ProgressCallback | undefined")]
+    pub type JsProgressCallback;
+    // Function.prototype.call, the error is whatever the callback threw
+    #[wasm_bindgen(method, catch, js_name = call)]
+    pub fn call(this: &JsProgressCallback, this_arg: &JsValue, progress: &JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(typescript_type = "Schedule")]
+    pub type JsSchedule;
+    #[wasm_bindgen(typescript_type = "Schedule[]")]
+    pub type JsSchedules;
+    #[wasm_bindgen(typescript_type = "SolveResult")]
+    pub type JsSolveResult;
+    #[wasm_bindgen(typescript_type = "SearchStatus | undefined")]
+    pub type JsSearchStatus;
+    #[wasm_bindgen(typescript_type = "Progress")]
+    pub type JsProgress;
+    #[wasm_bindgen(typescript_type = "ClassNode | undefined")]
+    pub type JsClassNode;
+    #[wasm_bindgen(typescript_type = "ClassNode[]")]
+    pub type JsClassNodes;
+}
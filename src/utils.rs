@@ -1,5 +1,9 @@
 use std::{error::Error, fmt};
 
+use serde::Serialize;
+
+use crate::solver::Infeasible;
+
 // pub fn set_panic_hook() {
 //     // When the `console_error_panic_hook` feature is enabled, we can call the
 //     // `set_panic_hook` function at least once during initialization, and then
@@ -11,21 +15,157 @@ use std::{error::Error, fmt};
 //     console_error_panic_hook::set_once();
 // }
 
-#[derive(Debug)]
-pub struct SolveError {
-    pub msg: &'static str
+// ids are catalog ids, raw values are what the catalog actually had
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum SolveError {
+    // the catalog doesn't match the graphql schema, path is like classes.nodes[3].meetings[0].inSession
+    InvalidCatalog { path: String, reason: String },
+    InvalidPreferences { reason: String },
+    InvalidSchedule { reason: String },
+    MissingCourse { id: u64 },
+    // no lecture section matches a course code
+    UnknownCourse { code: String },
+    MissingSection { lecture_id: u64, section_id: u64 },
+    BadTime { section_id: u64, field: &'static str, raw: Option<String> },
+    BadDate { section_id: u64, field: &'static str, raw: String },
+    // exporting to a calendar needs the term dates
+    MissingDates { section_id: u64 },
+    UnknownMeetingType { id: u64 },
+    InvalidWalkingTimes { reason: String },
+    // the search came back empty, this is why
+    NoSchedule { explanation: Infeasible },
+    // results couldn't be handed back to js
+    Serialization { reason: String },
 }
 
 impl Error for SolveError {}
 
 impl fmt::Display for SolveError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.msg)
+        match self {
+            Self::InvalidCatalog { path, reason } => write!(f, "invalid course data at {}: {}", path, reason),
+            Self::InvalidPreferences { reason } => write!(f, "invalid preferences: {}", reason),
+            Self::InvalidSchedule { reason } => write!(f, "invalid schedule: {}", reason),
+            Self::MissingCourse { id } => write!(f, "course {} is not in the catalog", id),
+            Self::UnknownCourse { code } => write!(f, "no lecture sections of {} in the catalog", code),
+            Self::MissingSection { lecture_id, section_id } =>
+                write!(f, "section {} linked from lecture {} is not in the catalog", section_id, lecture_id),
+            Self::BadTime { section_id, field, raw: Some(raw) } =>
+                write!(f, "section {} has an invalid {} {:?}", section_id, field, raw),
+            Self::BadTime { section_id, field, raw: None } => write!(f, "section {} has no {}", section_id, field),
+            Self::BadDate { section_id, field, raw } =>
+                write!(f, "section {} has an invalid {} {:?}", section_id, field, raw),
+            Self::MissingDates { section_id } => write!(f, "section {} has no start and end dates", section_id),
+            Self::UnknownMeetingType { id } => write!(f, "meeting type {} is not in meetingTypes", id),
+            Self::NoSchedule { explanation } => write!(f, "no schedule fits: {}", explanation),
+            Self::InvalidWalkingTimes { reason } => write!(f, "invalid walking times: {}", reason),
+            Self::Serialization { reason } => write!(f, "could not serialize result: {}", reason),
+        }
+    }
+}
+
+// milliseconds on a clock that keeps going, Instant panics in the browser
+pub fn now_ms() -> f64 {
+    cfg_if::cfg_if! {
+        if #[cfg(target_arch = "wasm32")] {
+            js_sys::Date::now()
+        } else {
+            std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)
+                .map_or(0.0, |elapsed| elapsed.as_secs_f64() * 1000.0)
+        }
+    }
+}
+
+// what js gets back, the tagged error fields plus the display message
+#[derive(Serialize)]
+pub struct ErrorReport<'a> {
+    pub message: String,
+    #[serde(flatten)]
+    pub error: &'a SolveError
+}
+
+impl<'a> From<&'a SolveError> for ErrorReport<'a> {
+    fn from(error: &'a SolveError) -> Self {
+        Self { message: error.to_string(), error }
+    }
+}
+
+// fixed size set of small indices, used for schedule masks and conflict rows
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitSet {
+    words: Vec<u64>
+}
+
+impl BitSet {
+    pub fn new(len: usize) -> Self {
+        Self { words: vec![0; len.div_ceil(64)] }
+    }
+
+    pub fn insert(&mut self, idx: usize) {
+        self.words[idx / 64] |= 1 << (idx % 64);
+    }
+
+    pub fn remove(&mut self, idx: usize) {
+        self.words[idx / 64] &= !(1 << (idx % 64));
+    }
+
+    pub fn contains(&self, idx: usize) -> bool {
+        self.words.get(idx / 64).is_some_and(|word| word & (1 << (idx % 64)) != 0)
+    }
+
+    pub fn intersects(&self, other: &BitSet) -> bool {
+        self.words.iter().zip(&other.words).any(|(a, b)| a & b != 0)
+    }
+
+    pub fn count_common(&self, other: &BitSet) -> usize {
+        self.words.iter().zip(&other.words).map(|(a, b)| (a & b).count_ones() as usize).sum()
+    }
+
+    // ascending order
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, &word)| {
+            let mut word = word;
+            std::iter::from_fn(move || {
+                if word == 0 { return None; }
+                let bit = word.trailing_zeros() as usize;
+                word &= word - 1;
+                Some(i * 64 + bit)
+            })
+        })
     }
 }
 
-impl From<&'static str> for SolveError {
-    fn from(value: &'static str) -> Self {
-        Self { msg: value }
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::utils::{BitSet, ErrorReport, SolveError};
+
+    #[test]
+    fn error_report_is_tagged() {
+        let err = SolveError::MissingSection { lecture_id: 1, section_id: 3 };
+        assert_eq!(serde_json::to_value(ErrorReport::from(&err)).unwrap(), json!({
+            "kind": "missingSection",
+            "lectureId": 1,
+            "sectionId": 3,
+            "message": "section 3 linked from lecture 1 is not in the catalog"
+        }));
+    }
+
+    #[test]
+    fn bitset_across_words() {
+        let mut a = BitSet::new(130);
+        let mut b = BitSet::new(130);
+        [3, 64, 129].iter().for_each(|&idx| a.insert(idx));
+        b.insert(65);
+
+        assert_eq!(a.iter().collect::<Vec<_>>(), vec![3, 64, 129]);
+        assert!(a.contains(64) && !a.contains(65) && !a.contains(500));
+        assert!(!a.intersects(&b));
+
+        b.insert(129);
+        assert!(a.intersects(&b));
+        a.remove(129);
+        assert!(!a.intersects(&b));
     }
 }
@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::utils::SolveError;
+
+// seconds it takes to walk between two buildings, the same either way.
+// json looks like { "COB": { "SE": 420 } }, csv is one from,to,seconds line per pair
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(from = "HashMap<String, HashMap<String, u64>>")]
+pub struct WalkingTimes {
+    times: HashMap<(String, String), u64>,
+}
+
+impl WalkingTimes {
+    pub fn insert(&mut self, from: &str, to: &str, seconds: u64) {
+        let (from, to) = (normalize(from), normalize(to));
+        self.times.insert((from.clone(), to.clone()), seconds);
+        self.times.insert((to, from), seconds);
+    }
+
+    // staying in the same building takes no time unless the table says otherwise,
+    // None means the pair is unknown and anything goes
+    pub fn between(&self, from: &str, to: &str) -> Option<u64> {
+        let (from, to) = (normalize(from), normalize(to));
+        match self.times.get(&(from.clone(), to.clone())) {
+            Some(&seconds) => Some(seconds),
+            None if from == to => Some(0),
+            None => None,
+        }
+    }
+
+    pub fn longest(&self) -> u64 {
+        self.times.values().copied().max().unwrap_or(0)
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, SolveError> {
+        serde_json::from_str(text).map_err(|err| SolveError::InvalidWalkingTimes { reason: err.to_string() })
+    }
+
+    // an optional from,to,seconds header, blank lines and # comments are skipped
+    pub fn from_csv(text: &str) -> Result<Self, SolveError> {
+        let mut walking_times = Self::default();
+        let mut first = true;
+
+        for (line_idx, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') { continue; }
+            let maybe_header = std::mem::replace(&mut first, false);
+
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let [from, to, seconds] = fields.as_slice() else {
+                return Err(SolveError::InvalidWalkingTimes {
+                    reason: format!("line {}: expected from,to,seconds", line_idx + 1)
+                });
+            };
+
+            match seconds.parse() {
+                Ok(seconds) => walking_times.insert(from, to, seconds),
+                Err(_) if maybe_header && seconds.eq_ignore_ascii_case("seconds") => continue,
+                Err(_) => return Err(SolveError::InvalidWalkingTimes {
+                    reason: format!("line {}: {:?} is not a number of seconds", line_idx + 1, seconds)
+                }),
+            }
+        }
+
+        Ok(walking_times)
+    }
+}
+
+impl From<HashMap<String, HashMap<String, u64>>> for WalkingTimes {
+    fn from(value: HashMap<String, HashMap<String, u64>>) -> Self {
+        let mut walking_times = Self::default();
+        for (from, destinations) in value {
+            for (to, seconds) in destinations {
+                walking_times.insert(&from, &to, seconds);
+            }
+        }
+        walking_times
+    }
+}
+
+fn normalize(building: &str) -> String {
+    building.trim().to_ascii_uppercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::SolveError;
+    use crate::walking::WalkingTimes;
+
+    #[test]
+    fn json_and_csv_agree() {
+        let from_json = WalkingTimes::from_json(r#"{ "COB": { "SE": 420 }, "KL": { "cob": 300 } }"#).unwrap();
+        let from_csv = WalkingTimes::from_csv("from,to,seconds\n# main quad\nCOB, SE, 420\n\nKL,COB,300\n").unwrap();
+
+        for walking_times in [from_json, from_csv] {
+            assert_eq!(walking_times.between("se", "COB"), Some(420));
+            assert_eq!(walking_times.between("COB", "KL"), Some(300));
+            assert_eq!(walking_times.between("SE", "SE"), Some(0));
+            assert_eq!(walking_times.between("SE", "KL"), None);
+            assert_eq!(walking_times.longest(), 420);
+        }
+    }
+
+    #[test]
+    fn bad_csv_names_the_line() {
+        assert_eq!(WalkingTimes::from_csv("COB,SE,420\nCOB,KL,soon").unwrap_err(),
+            SolveError::InvalidWalkingTimes { reason: "line 2: \"soon\" is not a number of seconds".into() });
+        assert!(WalkingTimes::from_csv("COB,SE").is_err());
+    }
+}